@@ -0,0 +1,576 @@
+use crate::entities::glossary::GlossaryMode;
+use crate::entities::line::{IndentRules, SourceRules};
+use crate::entities::punctuation::PunctuationRules;
+use crate::entities::scene_break::SceneBreakStyle;
+use crate::entities::syntax::KentenStyle;
+use crate::entities::width::WidthRules;
+use crate::errors::ConfigError;
+use crate::format::{BlankLinePolicy, Format, Options};
+use crate::import::Dialect;
+use std::convert::TryInto;
+use std::fs;
+use std::path::{Path, PathBuf};
+use toml::value::{Table, Value};
+
+/// Name of project configuration file
+pub const FILE_NAME: &str = "naromat.toml";
+
+/// Project configuration written in `naromat.toml`
+///
+/// Every key is optional, and a missing key has the same value as the default of command line.
+/// Paths of glossaries are resolved from the directory of the configuration file.
+///
+/// ```toml
+/// [input]
+/// ext = "txt"
+/// dialect = "naromat"        # or "aozora"
+/// glossary = ["terms.toml"]  # or a path
+/// glossary_mode = "first"    # or "every"
+/// strict = true
+/// comment = "#"              # prefix of a comment line, or "" for no comment line
+///
+/// [output]
+/// target = "epub"
+/// ext = "txt"                # extension of saved files instead of the one of target
+/// title = "吾輩は猫である"
+/// author = "夏目漱石"
+/// vertical = true
+/// preamble = false
+/// combine_sections = false
+///
+/// [kenten]
+/// style = "sesame"           # dot, sesame, open-circle or filled-circle
+///
+/// [scene_break]
+/// markers = ["†", "◇"]
+/// marker = "◇◆◇"
+/// blank_run = 3
+/// padding = 10
+/// margin = 1
+/// aozora_page_break = false  # indent markers instead of `［＃改ページ］` in aozora
+///
+/// [blank_lines]
+/// policy = "collapse"        # preserve, paragraph, transition or collapse
+/// max = 1
+///
+/// [indent]
+/// indent = "　"
+/// rules = [["「", ""], ["――", "　"]]
+/// preserve = true
+///
+/// [exclamation]
+/// space = "　"
+///
+/// [punctuation]              # every rule is enabled unless it is false
+/// dash = false
+///
+/// [width]                    # rules for the target, which are overwritten by the keys
+/// latin = false
+///
+/// [width.epub]               # keys only for a target
+/// latin = true
+/// ```
+///
+/// `[punctuation]` and `[width]` are applied when the table is written, unless `enabled = false`.
+#[derive(Debug, Clone)]
+pub struct Config {
+    /// Extension of source files
+    pub ext: String,
+    /// Extension of saved files, or `None` to follow the target format
+    pub output_ext: Option<String>,
+    pub dialect: Dialect,
+    /// Rules of how comments and sentences in source are read
+    pub source_rules: SourceRules,
+    pub target: Format,
+    /// Glossary files which are merged in order
+    pub glossaries: Vec<PathBuf>,
+    pub glossary_mode: GlossaryMode,
+    /// Fail without saving when source has markup errors
+    pub strict: bool,
+    pub options: Options,
+    pub kenten: KentenStyle,
+    pub scene_breaks: SceneBreakStyle,
+    pub indent: IndentRules,
+    /// Whitespace inserted after exclamation
+    pub exclamation_space: String,
+    /// Rules to normalize punctuation, or `None` to keep punctuation in source
+    pub punctuation: Option<PunctuationRules>,
+    /// Whole document, which `[width]` is read from per target
+    document: Table,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            ext: "txt".to_string(),
+            output_ext: None,
+            dialect: Dialect::default(),
+            source_rules: SourceRules::default(),
+            target: Format::default(),
+            glossaries: Vec::new(),
+            glossary_mode: GlossaryMode::default(),
+            strict: false,
+            options: Options::default(),
+            kenten: KentenStyle::default(),
+            scene_breaks: SceneBreakStyle::default(),
+            indent: IndentRules::default(),
+            exclamation_space: "　".to_string(),
+            punctuation: None,
+            document: Table::new(),
+        }
+    }
+}
+
+impl Config {
+    /// Find configuration file in the directory of source or its ancestors
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use naromat::config::Config;
+    /// use std::path::Path;
+    ///
+    /// let path = Config::find(Path::new("./path/to/source/file.txt")).unwrap();
+    /// ```
+    pub fn find(source: &Path) -> Option<PathBuf> {
+        let source = source.canonicalize().unwrap_or_else(|_| source.to_path_buf());
+        let start = if source.is_dir() {
+            source.as_path()
+        } else {
+            source.parent()?
+        };
+        start
+            .ancestors()
+            .map(|dir| dir.join(FILE_NAME))
+            .find(|path| path.is_file())
+    }
+
+    /// Load a configuration file
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use naromat::config::Config;
+    /// use std::path::Path;
+    ///
+    /// let config = Config::load(Path::new("./path/to/naromat.toml")).unwrap();
+    /// ```
+    pub fn load(path: &Path) -> Result<Self, ConfigError> {
+        let text = fs::read_to_string(path).map_err(ConfigError::CannotRead)?;
+        Self::from_toml(&text, path.parent().unwrap_or_else(|| Path::new("")))
+    }
+
+    /// Parse configuration written in TOML, whose relative paths are resolved from `root`
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use naromat::config::Config;
+    /// use naromat::format::Format;
+    /// use std::path::Path;
+    ///
+    /// let config = Config::from_toml("[output]\ntarget = \"kakuyomu\"", Path::new("./")).unwrap();
+    /// assert_eq!(config.target, Format::Kakuyomu);
+    /// assert_eq!(config.ext, "txt");
+    /// assert!(Config::from_toml("[output]\ntarget = \"word\"", Path::new("./")).is_err());
+    /// ```
+    pub fn from_toml(text: &str, root: &Path) -> Result<Self, ConfigError> {
+        let document = match text.parse().map_err(ConfigError::InvalidToml)? {
+            Value::Table(document) => document,
+            _ => return Err(ConfigError::InvalidValue("document".to_string())),
+        };
+        let mut config = Self::default();
+
+        let input = Section::new(&document, "input")?;
+        if let Some(ext) = input.string("ext")? {
+            config.ext = ext;
+        }
+        if let Some(dialect) = input.choice("dialect", dialect)? {
+            config.dialect = dialect;
+        }
+        if let Some(glossaries) = input.strings("glossary")? {
+            config.glossaries = glossaries.iter().map(|path| root.join(path)).collect();
+        }
+        if let Some(mode) = input.choice("glossary_mode", glossary_mode)? {
+            config.glossary_mode = mode;
+        }
+        config.strict = input.boolean("strict")?.unwrap_or(false);
+        if let Some(comment) = input.string("comment")? {
+            config.source_rules.comment = comment;
+        }
+
+        let output = Section::new(&document, "output")?;
+        if let Some(target) = output.choice("target", |name| name.parse().ok())? {
+            config.target = target;
+        }
+        config.output_ext = output.string("ext")?;
+        config.options.title = output.string("title")?;
+        config.options.author = output.string("author")?;
+        config.options.vertical = output.boolean("vertical")?.unwrap_or(false);
+        config.options.preamble = output.boolean("preamble")?.unwrap_or(false);
+        config.options.combine_sections = output.boolean("combine_sections")?.unwrap_or(false);
+
+        if let Some(style) = Section::new(&document, "kenten")?.choice("style", kenten_style)? {
+            config.kenten = style;
+        }
+
+        let scene_break = Section::new(&document, "scene_break")?;
+        if let Some(markers) = scene_break.strings("markers")? {
+            config.scene_breaks.markers = markers;
+        }
+        config.scene_breaks.blank_run = scene_break.number("blank_run")?;
+        config.scene_breaks.marker = scene_break.string("marker")?;
+        if let Some(padding) = scene_break.number("padding")? {
+            config.scene_breaks.padding = padding;
+        }
+        if let Some(margin) = scene_break.number("margin")? {
+            config.scene_breaks.margin = margin;
+        }
+        if let Some(page_break) = scene_break.boolean("aozora_page_break")? {
+            config.scene_breaks.aozora_page_break = page_break;
+        }
+
+        let blank_lines = Section::new(&document, "blank_lines")?;
+        let max = blank_lines.number("max")?.unwrap_or(1);
+        if let Some(policy) = blank_lines.choice("policy", |name| blank_line_policy(name, max))? {
+            config.options.blank_lines = policy;
+        }
+
+        let indent = Section::new(&document, "indent")?;
+        if let Some(text) = indent.string("indent")? {
+            config.indent.indent = text;
+        }
+        if let Some(rules) = indent.pairs("rules")? {
+            config.indent.heads = rules;
+        }
+        if let Some(preserve) = indent.boolean("preserve")? {
+            config.indent.preserve = preserve;
+        }
+
+        if let Some(space) = Section::new(&document, "exclamation")?.string("space")? {
+            config.exclamation_space = space;
+        }
+
+        let punctuation = Section::new(&document, "punctuation")?;
+        if punctuation.is_enabled()? {
+            let rules = PunctuationRules::default();
+            config.punctuation = Some(PunctuationRules {
+                ellipsis: punctuation.boolean("ellipsis")?.unwrap_or(rules.ellipsis),
+                dash: punctuation.boolean("dash")?.unwrap_or(rules.dash),
+                even_marks: punctuation.boolean("even_marks")?.unwrap_or(rules.even_marks),
+                exclamation: punctuation.boolean("exclamation")?.unwrap_or(rules.exclamation),
+                double_exclamation: punctuation
+                    .boolean("double_exclamation")?
+                    .unwrap_or(rules.double_exclamation),
+                period_before_bracket: punctuation
+                    .boolean("period_before_bracket")?
+                    .unwrap_or(rules.period_before_bracket),
+            });
+        }
+
+        config.document = document;
+        let width = Section::new(&config.document, "width")?;
+        width.is_enabled()?;
+        for (key, value) in width.table.into_iter().flatten() {
+            let format = match value {
+                Value::Table(_) => key
+                    .parse()
+                    .map_err(|_| ConfigError::InvalidValue(format!("width.{}", key)))?,
+                _ => Format::default(),
+            };
+            config.width_rules(format)?;
+        }
+        Ok(config)
+    }
+
+    /// Rules to normalize width for designated target, or `None` to keep width in source
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use naromat::config::Config;
+    /// use naromat::format::Format;
+    /// use std::path::Path;
+    ///
+    /// let config = Config::from_toml("[width]\n[width.epub]\nlatin = true", Path::new("./")).unwrap();
+    /// assert!(config.width_for(Format::Epub).unwrap().latin);
+    /// assert!(!config.width_for(Format::Narou).unwrap().latin);
+    /// assert!(Config::default().width_for(Format::Epub).is_none());
+    /// ```
+    pub fn width_for(&self, format: Format) -> Option<WidthRules> {
+        let width = Section::new(&self.document, "width").ok()?;
+        match width.is_enabled() {
+            Ok(true) => self.width_rules(format).ok(),
+            _ => None,
+        }
+    }
+
+    /// Rules to normalize width for designated target even if `[width]` is not enabled
+    ///
+    /// These rules are used when normalization is turned on in command line.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use naromat::config::Config;
+    /// use naromat::format::Format;
+    /// use std::path::Path;
+    ///
+    /// let config = Config::from_toml("[width]\nenabled = false\nlatin = true", Path::new("./")).unwrap();
+    /// assert!(config.width_for(Format::Narou).is_none());
+    /// assert!(config.width_rules_for(Format::Narou).latin);
+    /// ```
+    pub fn width_rules_for(&self, format: Format) -> WidthRules {
+        self.width_rules(format)
+            .unwrap_or_else(|_| WidthRules::for_format(format))
+    }
+
+    /// Rules to normalize width which are overwritten by `[width]` and `[width.<target>]`
+    fn width_rules(&self, format: Format) -> Result<WidthRules, ConfigError> {
+        let width = Section::new(&self.document, "width")?;
+        let target = Section::new(&self.document, &format!("width.{}", format))?;
+        let mut rules = WidthRules::for_format(format);
+        for section in [width, target].iter() {
+            rules = WidthRules {
+                digits: section.boolean("digits")?.unwrap_or(rules.digits),
+                tate_chu_yoko: section.boolean("tate_chu_yoko")?.unwrap_or(rules.tate_chu_yoko),
+                katakana: section.boolean("katakana")?.unwrap_or(rules.katakana),
+                latin: section.boolean("latin")?.unwrap_or(rules.latin),
+            };
+        }
+        Ok(rules)
+    }
+}
+
+/// Table of configuration whose invalid values are reported with its dotted name
+struct Section<'a> {
+    name: String,
+    table: Option<&'a Table>,
+}
+
+impl<'a> Section<'a> {
+    /// Table of designated dotted name, which may not be written
+    fn new(document: &'a Table, name: &str) -> Result<Self, ConfigError> {
+        let mut table = Some(document);
+        for key in name.split('.') {
+            table = match table.and_then(|table| table.get(key)) {
+                Some(Value::Table(child)) => Some(child),
+                Some(_) => return Err(ConfigError::InvalidValue(name.to_string())),
+                None => None,
+            };
+        }
+        Ok(Self {
+            name: name.to_string(),
+            table,
+        })
+    }
+
+    /// Return true if the table is written and not disabled by `enabled = false`
+    fn is_enabled(&self) -> Result<bool, ConfigError> {
+        Ok(self.table.is_some() && self.boolean("enabled")?.unwrap_or(true))
+    }
+
+    fn string(&self, key: &str) -> Result<Option<String>, ConfigError> {
+        self.get(key, |value| value.as_str().map(String::from))
+    }
+
+    fn boolean(&self, key: &str) -> Result<Option<bool>, ConfigError> {
+        self.get(key, Value::as_bool)
+    }
+
+    fn number(&self, key: &str) -> Result<Option<usize>, ConfigError> {
+        self.get(key, |value| {
+            value.as_integer().and_then(|number| number.try_into().ok())
+        })
+    }
+
+    /// A string or an array of strings
+    fn strings(&self, key: &str) -> Result<Option<Vec<String>>, ConfigError> {
+        self.get(key, |value| match value {
+            Value::String(string) => Some(vec![string.clone()]),
+            Value::Array(array) => array.iter().map(|value| value.as_str().map(String::from)).collect(),
+            _ => None,
+        })
+    }
+
+    /// An array of pairs of strings
+    fn pairs(&self, key: &str) -> Result<Option<Vec<(String, String)>>, ConfigError> {
+        self.get(key, |value| {
+            value
+                .as_array()?
+                .iter()
+                .map(|pair| match pair.as_array()?.as_slice() {
+                    [Value::String(first), Value::String(second)] => Some((first.clone(), second.clone())),
+                    _ => None,
+                })
+                .collect()
+        })
+    }
+
+    /// A string which is one of names parsed by the function
+    fn choice<T>(&self, key: &str, parse: impl Fn(&str) -> Option<T>) -> Result<Option<T>, ConfigError> {
+        self.get(key, |value| value.as_str().and_then(&parse))
+    }
+
+    /// Value of a key converted by the function, which fails if the value cannot be converted
+    fn get<T>(&self, key: &str, convert: impl Fn(&Value) -> Option<T>) -> Result<Option<T>, ConfigError> {
+        match self.table.and_then(|table| table.get(key)) {
+            Some(value) => match convert(value) {
+                Some(converted) => Ok(Some(converted)),
+                None => Err(ConfigError::InvalidValue(format!("{}.{}", self.name, key))),
+            },
+            None => Ok(None),
+        }
+    }
+}
+
+fn dialect(name: &str) -> Option<Dialect> {
+    match name {
+        "naromat" => Some(Dialect::Naromat),
+        "aozora" => Some(Dialect::Aozora),
+        _ => None,
+    }
+}
+
+fn glossary_mode(name: &str) -> Option<GlossaryMode> {
+    match name {
+        "every" => Some(GlossaryMode::Every),
+        "first" => Some(GlossaryMode::First),
+        _ => None,
+    }
+}
+
+fn kenten_style(name: &str) -> Option<KentenStyle> {
+    match name {
+        "dot" => Some(KentenStyle::Dot),
+        "sesame" => Some(KentenStyle::Sesame),
+        "open-circle" => Some(KentenStyle::OpenCircle),
+        "filled-circle" => Some(KentenStyle::FilledCircle),
+        _ => None,
+    }
+}
+
+fn blank_line_policy(name: &str, max: usize) -> Option<BlankLinePolicy> {
+    match name {
+        "preserve" => Some(BlankLinePolicy::Preserve),
+        "paragraph" => Some(BlankLinePolicy::EveryParagraph),
+        "transition" => Some(BlankLinePolicy::Transition),
+        "collapse" => Some(BlankLinePolicy::Collapse(max)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn from_toml_should_keep_defaults_of_missing_keys() {
+        let config = Config::from_toml("", Path::new("./")).unwrap();
+        assert_eq!(config.ext, "txt");
+        assert_eq!(config.target, Format::Narou);
+        assert_eq!(config.scene_breaks, SceneBreakStyle::default());
+        assert_eq!(config.indent, IndentRules::default());
+        assert_eq!(config.exclamation_space, "　");
+        assert_eq!(config.punctuation, None);
+    }
+
+    #[test]
+    fn from_toml_should_parse_transforms() {
+        let text = "\
+[input]
+ext = \"md\"
+glossary = [\"terms.tsv\", \"/abs/names.toml\"]
+glossary_mode = \"first\"
+comment = \"#\"
+
+[kenten]
+style = \"open-circle\"
+
+[scene_break]
+markers = \"†\"
+blank_run = 3
+aozora_page_break = false
+
+[blank_lines]
+policy = \"collapse\"
+max = 2
+
+[indent]
+rules = [[\"「\", \"\"]]
+preserve = false
+
+[exclamation]
+space = \"\"
+
+[punctuation]
+dash = false
+";
+        let config = Config::from_toml(text, Path::new("/project")).unwrap();
+        assert_eq!(config.ext, "md");
+        assert_eq!(
+            config.glossaries,
+            vec![PathBuf::from("/project/terms.tsv"), PathBuf::from("/abs/names.toml")]
+        );
+        assert_eq!(config.glossary_mode, GlossaryMode::First);
+        assert_eq!(config.source_rules.comment, "#");
+        assert_eq!(config.kenten, KentenStyle::OpenCircle);
+        assert_eq!(config.scene_breaks.markers, vec!["†".to_string()]);
+        assert_eq!(config.scene_breaks.blank_run, Some(3));
+        assert!(!config.scene_breaks.aozora_page_break);
+        assert_eq!(config.options.blank_lines, BlankLinePolicy::Collapse(2));
+        assert_eq!(config.indent.heads, vec![("「".to_string(), String::new())]);
+        assert!(!config.indent.preserve);
+        assert_eq!(config.exclamation_space, "");
+        assert_eq!(
+            config.punctuation,
+            Some(PunctuationRules {
+                dash: false,
+                ..PunctuationRules::default()
+            })
+        );
+    }
+
+    #[test]
+    fn from_toml_should_report_key_of_invalid_value() {
+        let error = Config::from_toml("[scene_break]\npadding = -1", Path::new("./")).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Invalid value error: Value of scene_break.padding is invalid"
+        );
+        let error = Config::from_toml("[width.word]\nlatin = true", Path::new("./")).unwrap_err();
+        assert_eq!(error.to_string(), "Invalid value error: Value of width.word is invalid");
+        let error = Config::from_toml("[width.epub]\nlatin = 1", Path::new("./")).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Invalid value error: Value of width.epub.latin is invalid"
+        );
+    }
+
+    #[test]
+    fn from_toml_should_disable_written_table() {
+        let config = Config::from_toml(
+            "[punctuation]\nenabled = false\n[width]\nenabled = false",
+            Path::new("./"),
+        );
+        let config = config.unwrap();
+        assert_eq!(config.punctuation, None);
+        assert_eq!(config.width_for(Format::Html), None);
+    }
+
+    #[test]
+    fn find_should_find_config_in_ancestor() {
+        // given
+        let source = Path::new("./resources/test/config/project/episodes/1.txt");
+
+        // when
+        let found = Config::find(source).unwrap();
+
+        // then
+        let expected = Path::new("./resources/test/config/project/naromat.toml");
+        assert_eq!(found, expected.canonicalize().unwrap());
+        let config = Config::load(&found).unwrap();
+        assert_eq!(config.target, Format::Kakuyomu);
+        assert_eq!(config.glossaries, vec![found.parent().unwrap().join("glossary.tsv")]);
+    }
+}
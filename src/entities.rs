@@ -1,4 +1,11 @@
+pub mod book;
 pub mod chapter;
+pub mod glossary;
 pub mod line;
+pub mod punctuation;
+pub mod ruby_index;
+pub mod scene_break;
 pub mod sentence;
+pub mod syntax;
 pub mod text_file;
+pub mod width;
@@ -0,0 +1,160 @@
+use crate::entities::chapter::Chapter;
+use crate::entities::text_file::TextFile;
+use crate::errors::TextFileOutputError;
+use crate::format::epub::{self, ContentDocument};
+use crate::format::{Format, Options};
+use std::io;
+
+/// Structure of novel book
+///
+/// Books are defined below:
+/// * Consist of episodes in the order they were added
+/// * Each episode has its own title and chapter
+///
+#[derive(Default)]
+pub struct Book {
+    episodes: Vec<(String, Chapter)>,
+}
+
+/// Implementation for novel book structure
+impl<'file_handling> Book {
+    /// Constructor
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use naromat::entities::book::Book;
+    ///
+    /// Book::new();
+    /// ```
+    pub fn new() -> Self {
+        Self { episodes: Vec::new() }
+    }
+
+    /// Add an episode to the end of this book
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use naromat::entities::book::Book;
+    /// use naromat::entities::chapter::Chapter;
+    ///
+    /// let mut book = Book::new();
+    /// book.push("第一話", Chapter::new("我が輩は猫である。"));
+    /// assert_eq!(book.len(), 1);
+    /// ```
+    pub fn push(&mut self, title: &str, chapter: Chapter) {
+        self.episodes.push((title.to_string(), chapter));
+    }
+
+    /// Number of episodes
+    pub fn len(&self) -> usize {
+        self.episodes.len()
+    }
+
+    /// Return true if this book has no episode
+    pub fn is_empty(&self) -> bool {
+        self.episodes.is_empty()
+    }
+
+    /// Format episodes and save as an EPUB3 book
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use naromat::entities::book::Book;
+    /// use naromat::entities::chapter::Chapter;
+    /// use naromat::format::Options;
+    ///
+    /// let mut book = Book::new();
+    /// book.push("第一話", Chapter::new("我が輩は猫である。"));
+    /// let options = Options {
+    ///     title: Some("吾輩は猫である".to_string()),
+    ///     author: Some("夏目漱石".to_string()),
+    ///     vertical: true,
+    ///     ..Options::default()
+    /// };
+    /// book.save_as_epub("./path/to/save.epub", &options);
+    /// ```
+    pub fn save_as_epub(
+        self,
+        path_to: &'file_handling str,
+        options: &Options,
+    ) -> Result<(), TextFileOutputError<'file_handling>> {
+        let file = TextFile::touch_file(path_to)?;
+        let documents: Vec<ContentDocument> = self
+            .episodes
+            .into_iter()
+            .map(|(title, chapter)| {
+                let document_options = Options {
+                    title: Some(title.clone()),
+                    ..options.clone()
+                };
+                ContentDocument {
+                    xhtml: chapter.get_with(Format::Epub, &document_options),
+                    title,
+                }
+            })
+            .collect();
+        match epub::write(file, &documents, options) {
+            Ok(_) => Ok(()),
+            Err(cause) => Err(TextFileOutputError::CannotWrite(io::Error::from(cause))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Book;
+    use crate::entities::chapter::Chapter;
+    use crate::format::Options;
+    use std::fs;
+    use std::io::Read;
+    use zip::ZipArchive;
+
+    #[test]
+    fn can_save_as_epub() {
+        // given
+        let target_file_path = "./resources/test/entities/file/target-book.epub";
+        let mut book = Book::new();
+        book.push("第一話", Chapter::new("我が輩は[猫:ねこ]である。"));
+        book.push("第二話", Chapter::new("名前は[まだ:.]ない。"));
+
+        // when
+        book.save_as_epub(target_file_path, &Options::default()).unwrap();
+
+        // then
+        let mut archive = ZipArchive::new(fs::File::open(target_file_path).unwrap()).unwrap();
+        let mut first = String::new();
+        archive
+            .by_name("OEBPS/text/episode-0001.xhtml")
+            .unwrap()
+            .read_to_string(&mut first)
+            .unwrap();
+        let episode_count = archive
+            .file_names()
+            .filter(|name| name.starts_with("OEBPS/text/"))
+            .count();
+        // teardown
+        fs::remove_file(target_file_path).unwrap();
+        // assert
+        assert!(first.contains("<title>第一話</title>"));
+        assert!(first.contains("<p>　我が輩は<ruby>猫<rt>ねこ</rt></ruby>である。</p>"));
+        assert_eq!(episode_count, 2);
+    }
+
+    #[test]
+    fn return_error_when_target_file_already_exists() {
+        // given
+        let target_file_path = "./resources/test/entities/file/already_exists.txt";
+        let book = Book::new();
+
+        // expect
+        let is_return_error = book.save_as_epub(target_file_path, &Options::default()).is_err();
+        let is_target_file_not_modified = fs::metadata(target_file_path).unwrap().len() == 0;
+
+        // assert
+        assert!(is_return_error);
+        assert!(is_target_file_not_modified);
+    }
+}
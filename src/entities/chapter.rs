@@ -1,4 +1,15 @@
-use crate::entities::line::Line;
+use crate::entities::glossary::{Glossary, GlossaryMode};
+use crate::entities::line::{BlockComment, IndentRules, Line, SourceRules};
+use crate::entities::punctuation::PunctuationRules;
+use crate::entities::scene_break::{SceneBreak, SceneBreakStyle};
+use crate::entities::syntax::{self, KentenStyle, Node};
+use crate::entities::width::WidthRules;
+use crate::errors::{Diagnostic, MarkupError};
+use crate::format::{docx, epub, html, latex};
+use crate::format::{BlankLinePolicy, Format, Options};
+use crate::import::{aozora, Dialect};
+use std::collections::HashSet;
+use std::ops::Range;
 
 /// Structure of novel chapter.
 ///
@@ -7,7 +18,147 @@ use crate::entities::line::Line;
 /// * End with next chapter or end of document
 ///
 pub struct Chapter {
-    lines: Vec<Line>,
+    lines: Vec<(Section, Element)>,
+    scene_breaks: SceneBreakStyle,
+    source: String,
+    source_rules: SourceRules,
+}
+
+/// Element of a chapter, which is a line or a scene break
+enum Element {
+    Line(Line),
+    Break(SceneBreak),
+}
+
+impl Element {
+    /// Element of a source line, which is a page break or a line
+    fn new(text: &str) -> Self {
+        if SceneBreak::is_page_marker(text) {
+            return Element::Break(SceneBreak::Page);
+        }
+        Element::Line(Line::new(text))
+    }
+
+    /// Return true if this element is an empty line
+    fn is_empty_line(&self) -> bool {
+        match self {
+            Element::Line(line) => line.plain_text().is_some_and(|text| text.is_empty()),
+            Element::Break(_) => false,
+        }
+    }
+
+    /// Get string of element formatted to designated format
+    fn get_as(self, format: Format, style: &SceneBreakStyle) -> String {
+        match self {
+            Element::Line(line) => line.get_as(format),
+            Element::Break(scene_break) => scene_break.get_as(format, style),
+        }
+    }
+}
+
+/// Section of a chapter, which is a separate field of an episode in Narou
+///
+/// Sections are designated by marker lines below, and lines before every marker are body:
+/// * `@preface` or `@まえがき` - Preface
+/// * `@body` or `@本文` - Body
+/// * `@afterword` or `@あとがき` - Afterword
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Section {
+    Preface,
+    Body,
+    Afterword,
+}
+
+impl Section {
+    /// Section designated by a marker line
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use naromat::entities::chapter::Section;
+    ///
+    /// assert_eq!(Section::from_marker(" @afterword"), Some(Section::Afterword));
+    /// assert_eq!(Section::from_marker(r"\@afterword"), None);
+    /// ```
+    pub fn from_marker(text: &str) -> Option<Self> {
+        match text.trim() {
+            "@preface" | "@まえがき" => Some(Section::Preface),
+            "@body" | "@本文" => Some(Section::Body),
+            "@afterword" | "@あとがき" => Some(Section::Afterword),
+            _ => None,
+        }
+    }
+
+    /// Name of this section which is used in file names(`episode.preface.txt`)
+    pub fn name(self) -> &'static str {
+        match self {
+            Section::Preface => "preface",
+            Section::Body => "body",
+            Section::Afterword => "afterword",
+        }
+    }
+
+    /// Heading which separates this section in a combined document
+    pub fn heading(self) -> &'static str {
+        match self {
+            Section::Preface => "【まえがき】",
+            Section::Body => "【本文】",
+            Section::Afterword => "【あとがき】",
+        }
+    }
+
+    /// Heading formatted to designated format, which is `[chapter:まえがき]` in pixiv
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use naromat::entities::chapter::Section;
+    /// use naromat::format::Format;
+    ///
+    /// assert_eq!(Section::Preface.heading_as(Format::Narou), "【まえがき】");
+    /// assert_eq!(Section::Preface.heading_as(Format::Pixiv), "[chapter:まえがき]");
+    /// ```
+    pub fn heading_as(self, format: Format) -> String {
+        match format {
+            Format::Pixiv => format!("[chapter:{}]", self.heading().trim_matches(|c| c == '【' || c == '】')),
+            _ => self.heading().to_string(),
+        }
+    }
+
+    /// Split lines by marker lines
+    ///
+    /// Empty lines around markers are dropped.
+    fn split(lines: Vec<&str>) -> Vec<(Section, &str)> {
+        if !lines.iter().any(|line| Self::from_marker(line).is_some()) {
+            return lines.into_iter().map(|line| (Section::Body, line)).collect();
+        }
+        let mut sections: Vec<(Section, Vec<&str>)> = vec![(Section::Body, Vec::new())];
+        for line in lines {
+            match Self::from_marker(line) {
+                Some(section) => sections.push((section, Vec::new())),
+                None => sections.last_mut().unwrap().1.push(line),
+            }
+        }
+        let mut split: Vec<(Section, &str)> = sections
+            .into_iter()
+            .flat_map(|(section, lines)| {
+                let start = lines
+                    .iter()
+                    .position(|line| !line.trim().is_empty())
+                    .unwrap_or(lines.len());
+                let end = lines
+                    .iter()
+                    .rposition(|line| !line.trim().is_empty())
+                    .map_or(start, |end| end + 1);
+                lines[start..end.max(start)]
+                    .iter()
+                    .map(|line| (section, *line))
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+        split.sort_by_key(|(section, _)| *section);
+        split
+    }
 }
 
 /// Implementation for novel chapter structure
@@ -23,15 +174,334 @@ impl Chapter {
     ```
     */
     pub fn new(text: &str) -> Self {
-        Self {
-            lines: text
-                .split_terminator('\n')
-                .filter(|text| !Line::is_comment(text))
-                .map(|line| Line::new(line))
-                .collect(),
+        Self::new_with(text, Dialect::Naromat, &SourceRules::default())
+    }
+
+    /**
+    Constructor from text written in designated dialect
+    # Example
+    ```
+    use naromat::entities::chapter::Chapter;
+    use naromat::import::Dialect;
+    let chapter = Chapter::new_in("
+    ［＃ここから２字下げ］
+    我が輩は猫［＃「猫」に傍点］である。名前《なまえ》はまだない。
+    ［＃ここで字下げ終わり］", Dialect::Aozora);
+    assert_eq!(chapter.get(), "\n　　我が輩は｜猫《・》である。｜名前《なまえ》はまだない。");
+    ```
+    */
+    pub fn new_in(text: &str, dialect: Dialect) -> Self {
+        Self::new_with(text, dialect, &SourceRules::default())
+    }
+
+    /// Constructor from text written in designated dialect, whose comments and sentences are read with designated rules
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use naromat::entities::chapter::Chapter;
+    /// use naromat::entities::line::SourceRules;
+    /// use naromat::import::Dialect;
+    ///
+    /// let rules = SourceRules { comment: "#".to_string() };
+    /// let chapter = Chapter::new_with("# メモ\n// 本文", Dialect::Naromat, &rules);
+    /// assert_eq!(chapter.get(), "　// 本文");
+    /// ```
+    pub fn new_with(text: &str, dialect: Dialect, rules: &SourceRules) -> Self {
+        match dialect {
+            Dialect::Naromat => {
+                let body = Body::strip(text, rules);
+                let lines = Section::split(body.lines.into_iter().map(|(_, _, text)| text).collect())
+                    .into_iter()
+                    .map(|(section, text)| (section, Element::new(text)))
+                    .collect();
+                Self::with_elements(lines, text.to_string(), rules)
+            }
+            Dialect::Aozora => {
+                let lines = aozora::import(text)
+                    .into_iter()
+                    .filter(|(_, text)| !rules.is_comment(text))
+                    .map(|(indent, text)| match SceneBreak::is_page_marker(&text) {
+                        true => (Section::Body, Element::Break(SceneBreak::Page)),
+                        false => (Section::Body, Element::Line(Line::indented(&text, indent))),
+                    })
+                    .collect();
+                Self::with_elements(lines, String::new(), rules)
+            }
         }
     }
 
+    /// Add ruby of glossary terms
+    ///
+    /// An explicit ruby in source text overrides the glossary.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use naromat::entities::chapter::Chapter;
+    /// use naromat::entities::glossary::{Glossary, GlossaryMode};
+    ///
+    /// let mut glossary = Glossary::new();
+    /// glossary.insert("騎蜥", "きせき");
+    /// let chapter = Chapter::new("騎蜥が来た。\n騎蜥が去った。").with_glossary(&glossary, GlossaryMode::First);
+    /// assert_eq!(chapter.get(), "　｜騎蜥《きせき》が来た。\n　騎蜥が去った。");
+    /// ```
+    pub fn with_glossary(mut self, glossary: &Glossary, mode: GlossaryMode) -> Self {
+        let mut used = HashSet::new();
+        for line in self.lines_mut() {
+            line.apply_glossary(glossary, mode, &mut used);
+        }
+        self
+    }
+
+    /// Normalize punctuation with designated rules
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use naromat::entities::chapter::Chapter;
+    /// use naromat::entities::punctuation::PunctuationRules;
+    ///
+    /// let chapter = Chapter::new("「猫だ...!」").with_punctuation(&PunctuationRules::default());
+    /// assert_eq!(chapter.get(), "「猫だ……！」");
+    /// ```
+    pub fn with_punctuation(mut self, rules: &PunctuationRules) -> Self {
+        for line in self.lines_mut() {
+            line.apply_punctuation(rules);
+        }
+        self
+    }
+
+    /// Normalize width of digits, katakana and latin letters with designated rules
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use naromat::entities::chapter::Chapter;
+    /// use naromat::entities::width::WidthRules;
+    /// use naromat::format::Format;
+    ///
+    /// let chapter = Chapter::new("3ｶ月と12日").with_width(&WidthRules::for_format(Format::Html));
+    /// assert!(chapter.get_as(Format::Html).contains("<p>　３カ月と<span class=\"tcy\">12</span>日</p>"));
+    /// ```
+    pub fn with_width(mut self, rules: &WidthRules) -> Self {
+        for line in self.lines_mut() {
+            line.apply_width(rules);
+        }
+        self
+    }
+
+    /// Use designated whitespace after exclamation(`！`, `？`) instead of a 2 byte whitespace
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use naromat::entities::chapter::Chapter;
+    ///
+    /// let chapter = Chapter::new("猫！犬？").with_exclamation_space("");
+    /// assert_eq!(chapter.get(), "　猫！犬？");
+    /// ```
+    pub fn with_exclamation_space(mut self, space: &str) -> Self {
+        for line in self.lines_mut() {
+            line.apply_exclamation_space(space);
+        }
+        self
+    }
+
+    /// Indent paragraphs with designated rules
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use naromat::entities::chapter::Chapter;
+    /// use naromat::entities::line::IndentRules;
+    ///
+    /// let rules = IndentRules {
+    ///     heads: vec![("「".to_string(), String::new()), ("――".to_string(), "　".to_string())],
+    ///     ..IndentRules::default()
+    /// };
+    /// let chapter = Chapter::new("「猫だ」\n――猫である。\n　　吾輩は").with_indent_rules(&rules);
+    /// assert_eq!(chapter.get(), "「猫だ」\n　――猫である。\n　　吾輩は");
+    /// ```
+    pub fn with_indent_rules(mut self, rules: &IndentRules) -> Self {
+        for line in self.lines_mut() {
+            line.apply_indent_rules(rules);
+        }
+        self
+    }
+
+    /// Use designated style for kenten which has no explicit style(`[強調:.]`)
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use naromat::entities::chapter::Chapter;
+    /// use naromat::entities::syntax::KentenStyle;
+    ///
+    /// let chapter = Chapter::new("[猫:.]と[犬:.o]").with_kenten_style(KentenStyle::Sesame);
+    /// assert_eq!(chapter.get(), "　｜猫《﹅》と｜犬《◦》");
+    /// ```
+    pub fn with_kenten_style(mut self, style: KentenStyle) -> Self {
+        for line in self.lines_mut() {
+            line.apply_kenten_style(style);
+        }
+        self
+    }
+
+    /// Recognize and render scene breaks with designated style
+    ///
+    /// Lines of markers in the style, and runs of empty lines if `blank_run` is set, become scene breaks.
+    /// Scene breaks are never indented as paragraphs.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use naromat::entities::chapter::Chapter;
+    /// use naromat::entities::scene_break::SceneBreakStyle;
+    ///
+    /// let style = SceneBreakStyle {
+    ///     markers: vec!["†".to_string()],
+    ///     marker: Some("◇◆◇".to_string()),
+    ///     padding: 4,
+    ///     margin: 0,
+    ///     ..SceneBreakStyle::default()
+    /// };
+    /// let chapter = Chapter::new("我が輩は猫である。\n†\n名前はまだない。").with_scene_breaks(style);
+    /// assert_eq!(chapter.get(), "　我が輩は猫である。\n　　　　◇◆◇\n　名前はまだない。");
+    /// ```
+    pub fn with_scene_breaks(mut self, style: SceneBreakStyle) -> Self {
+        self.scene_breaks = style;
+        let lines = std::mem::take(&mut self.lines);
+        self.lines = Self::recognize_breaks(lines, &self.scene_breaks);
+        self
+    }
+
+    /// Return true if this chapter has preface or afterword
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use naromat::entities::chapter::Chapter;
+    ///
+    /// assert!(Chapter::new("@preface\n前書き\n@body\n本文").has_sections());
+    /// assert!(!Chapter::new("本文").has_sections());
+    /// ```
+    pub fn has_sections(&self) -> bool {
+        self.lines.iter().any(|(section, _)| *section != Section::Body)
+    }
+
+    /// Split this chapter to chapters of each section
+    ///
+    /// Body is always included, and preface or afterword is included only if it has lines.
+    /// Every split chapter has its lines as body.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use naromat::entities::chapter::{Chapter, Section};
+    ///
+    /// let sections = Chapter::new("本文\n@afterword\n後書き").into_sections();
+    /// assert_eq!(sections[0].0, Section::Body);
+    /// let (section, afterword) = sections.into_iter().nth(1).unwrap();
+    /// assert_eq!(section, Section::Afterword);
+    /// assert_eq!(afterword.get(), "　後書き");
+    /// ```
+    pub fn into_sections(self) -> Vec<(Section, Chapter)> {
+        let mut sections: Vec<(Section, Chapter)> = Vec::new();
+        for (section, line) in self.lines {
+            match sections.last_mut() {
+                Some((last, chapter)) if *last == section => chapter.lines.push((Section::Body, line)),
+                _ => sections.push((
+                    section,
+                    Chapter {
+                        lines: vec![(Section::Body, line)],
+                        scene_breaks: self.scene_breaks.clone(),
+                        source: String::new(),
+                        source_rules: self.source_rules.clone(),
+                    },
+                )),
+            }
+        }
+        if !sections.iter().any(|(section, _)| *section == Section::Body) {
+            let position = sections.iter().position(|(section, _)| *section > Section::Body);
+            let body = (
+                Section::Body,
+                Chapter {
+                    lines: Vec::new(),
+                    scene_breaks: self.scene_breaks,
+                    source: String::new(),
+                    source_rules: self.source_rules,
+                },
+            );
+            sections.insert(position.unwrap_or(sections.len()), body);
+        }
+        sections
+    }
+
+    /// Markup errors found in source text
+    ///
+    /// Errors are collected only from naromat source, not from other dialects.
+    /// An unterminated block comment is reported at its opening line.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use naromat::entities::chapter::Chapter;
+    ///
+    /// let chapter = Chapter::new("我が輩は猫である。\n　[東.京.都:とう.きょう]で生まれた。");
+    /// assert_eq!(chapter.diagnostics()[0].to_string(), "2:2: Ruby segment mismatch error: 3 parent segments for 2 ruby segments");
+    /// ```
+    pub fn diagnostics(&self) -> Vec<Diagnostic> {
+        self.locate(syntax::validate)
+    }
+
+    /// Markup errors found in source text, including violations of limits of designated format
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use naromat::entities::chapter::Chapter;
+    /// use naromat::format::Format;
+    ///
+    /// let chapter = Chapter::new("我が輩は猫である。\n[名前:なまえなまえなまえなまえ]はまだない。");
+    /// assert_eq!(chapter.diagnostics_for(Format::Narou)[0].to_string(), "2:1: Ruby too long error: 12 characters exceed the limit of 10");
+    /// assert!(chapter.diagnostics_for(Format::Html).is_empty());
+    /// ```
+    pub fn diagnostics_for(&self, format: Format) -> Vec<Diagnostic> {
+        self.locate(|nodes| syntax::validate_for(nodes, format))
+    }
+
+    /// Validate markup of each line except comment lines, and locate errors in source text
+    fn locate<F>(&self, validate: F) -> Vec<Diagnostic>
+    where
+        F: Fn(&[Node]) -> Vec<(Range<usize>, MarkupError)>,
+    {
+        let body = Body::strip(&self.source, &self.source_rules);
+        let mut diagnostics: Vec<Diagnostic> = body
+            .unterminated
+            .into_iter()
+            .map(|(index, line, block)| Diagnostic {
+                line: index + 1,
+                column: line.chars().count() - line.trim_start().chars().count() + 1,
+                error: MarkupError::UnterminatedBlockComment {
+                    closing: block.closing(),
+                },
+            })
+            .collect();
+        for (index, column, line) in body.lines {
+            let offset = line.len() - line.trim_start().len();
+            for (span, error) in validate(&syntax::parse(line.trim())) {
+                diagnostics.push(Diagnostic {
+                    line: index + 1,
+                    column: column + line[..offset + span.start].chars().count() + 1,
+                    error,
+                });
+            }
+        }
+        diagnostics.sort_by_key(|diagnostic| (diagnostic.line, diagnostic.column));
+        diagnostics
+    }
+
     /// Print formatted chapter
     ///
     /// # Example
@@ -46,8 +516,12 @@ impl Chapter {
     /// chapter.print()
     /// ```
     pub fn print(self) {
-        for line in self.lines {
-            line.print();
+        let style = self.scene_breaks.clone();
+        for element in self.into_lines(Format::Narou) {
+            match element {
+                Element::Line(line) => line.print(),
+                Element::Break(scene_break) => println!("{}", scene_break.get_as(Format::Narou, &style)),
+            }
         }
     }
 
@@ -66,13 +540,261 @@ impl Chapter {
     ```
     */
     pub fn get(self) -> String {
-        let text: Vec<String> = self.lines.into_iter().map(|line| line.get()).collect();
-        text.join("\n")
+        self.get_as(Format::Narou)
+    }
+
+    /**
+    Get string of chapter formatted to designated format
+    # Example
+    ```
+    use naromat::entities::chapter::Chapter;
+    use naromat::format::Format;
+    let chapter = Chapter::new("
+    どこで[生まれた:.]のかとんと[見当:けんとう]がつかぬ。
+    ");
+    assert_eq!(chapter.get_as(Format::Kakuyomu), "\n　どこで《《生まれた》》のかとんと｜見当《けんとう》がつかぬ。");
+    ```
+    */
+    pub fn get_as(self, format: Format) -> String {
+        self.get_with(format, &Options::default())
+    }
+
+    /**
+    Get string of chapter formatted to designated format with options
+    Empty lines between paragraphs follow `blank_lines` option.
+    # Example
+    ```
+    use naromat::entities::chapter::Chapter;
+    use naromat::format::{Format, Options};
+    let chapter = Chapter::new("どこで[生まれた:.]のかとんと[見当:けんとう]がつかぬ。");
+    let options = Options {
+        vertical: true,
+        ..Options::default()
+    };
+    let html = chapter.get_with(Format::Html, &options);
+    assert!(html.contains("writing-mode: vertical-rl;"));
+    assert!(html.contains("<p>　どこで<em class=\"kenten\">生まれた</em>のかとんと<ruby>見当<rt>けんとう</rt></ruby>がつかぬ。</p>"));
+    ```
+    */
+    pub fn get_with(self, format: Format, options: &Options) -> String {
+        let style = self.scene_breaks.clone();
+        let text: Vec<String> = Self::apply_blank_lines(self.into_lines(format), options.blank_lines, style.margin)
+            .into_iter()
+            .map(|element| element.get_as(format, &style))
+            .collect();
+        match format {
+            Format::Html => html::document(&text.join("\n"), options),
+            Format::Epub => epub::content_document(options.title.as_deref().unwrap_or_default(), &text.join("\n")),
+            Format::Latex if options.preamble => latex::document(&text.join("\n\n"), options),
+            Format::Latex => text.join("\n\n"),
+            Format::Docx => docx::document(&text.join("\n"), options),
+            _ => text.join("\n"),
+        }
+    }
+}
+
+impl Chapter {
+    /// Constructor from elements, recognizing scene breaks with default style
+    fn with_elements(lines: Vec<(Section, Element)>, source: String, rules: &SourceRules) -> Self {
+        let scene_breaks = SceneBreakStyle::default();
+        Self {
+            lines: Self::recognize_breaks(lines, &scene_breaks),
+            scene_breaks,
+            source,
+            source_rules: rules.clone(),
+        }
+    }
+
+    /// Lines of every section which are not scene breaks
+    fn lines_mut(&mut self) -> impl Iterator<Item = &mut Line> {
+        self.lines.iter_mut().filter_map(|(_, element)| match element {
+            Element::Line(line) => Some(line),
+            Element::Break(_) => None,
+        })
+    }
+
+    /// Recognize scene breaks in elements with designated style
+    ///
+    /// Scene breaks which are recognized before are restored to lines, and recognized again.
+    fn recognize_breaks(lines: Vec<(Section, Element)>, style: &SceneBreakStyle) -> Vec<(Section, Element)> {
+        let mut recognized = Vec::new();
+        let mut empty_lines: Vec<(Section, Element)> = Vec::new();
+        let restored = lines.into_iter().flat_map(|(section, element)| match element {
+            Element::Break(SceneBreak::Page) => vec![(section, Element::Break(SceneBreak::Page))],
+            Element::Break(scene_break) => scene_break
+                .source_lines()
+                .iter()
+                .map(|text| (section, Element::Line(Line::new(text))))
+                .collect(),
+            line => vec![(section, line)],
+        });
+        for (section, element) in restored {
+            let text = match element {
+                Element::Line(ref line) => line.plain_text(),
+                Element::Break(_) => None,
+            };
+            let is_same_section = empty_lines.last().is_none_or(|(last, _)| *last == section);
+            if text.as_deref() == Some("") && is_same_section {
+                empty_lines.push((section, element));
+                continue;
+            }
+            Self::push_empty_lines(&mut recognized, &mut empty_lines, style);
+            match text {
+                Some(text) if text.is_empty() => empty_lines.push((section, element)),
+                Some(text) if style.markers.contains(&text) => {
+                    let indent = match element {
+                        Element::Line(ref line) => line.indent(),
+                        Element::Break(_) => "",
+                    };
+                    let marker = format!("{}{}", indent, text);
+                    recognized.push((section, Element::Break(SceneBreak::Marker(marker))))
+                }
+                _ => recognized.push((section, element)),
+            }
+        }
+        Self::push_empty_lines(&mut recognized, &mut empty_lines, style);
+        recognized
+    }
+
+    /// Push a run of empty lines as a scene break if the run is long enough, otherwise as lines
+    fn push_empty_lines(
+        recognized: &mut Vec<(Section, Element)>,
+        empty_lines: &mut Vec<(Section, Element)>,
+        style: &SceneBreakStyle,
+    ) {
+        match (empty_lines.first(), style.blank_run) {
+            (Some((section, _)), Some(run)) if empty_lines.len() >= run.max(1) => {
+                recognized.push((*section, Element::Break(SceneBreak::Blank(empty_lines.len()))));
+                empty_lines.clear();
+            }
+            _ => recognized.append(empty_lines),
+        }
+    }
+
+    /// Insert or collapse empty lines between paragraphs with designated policy
+    ///
+    /// Empty lines are never inserted next to scene breaks which have their own margin.
+    fn apply_blank_lines(elements: Vec<Element>, policy: BlankLinePolicy, margin: usize) -> Vec<Element> {
+        let mut lines: Vec<Element> = Vec::new();
+        let mut empty_run = 0;
+        for element in elements {
+            if element.is_empty_line() {
+                empty_run += 1;
+                match policy {
+                    BlankLinePolicy::Collapse(max) if empty_run > max => (),
+                    _ => lines.push(element),
+                }
+                continue;
+            }
+            let needs_empty_line = match (lines.last(), &element, policy) {
+                (Some(Element::Line(_)), Element::Line(_), BlankLinePolicy::EveryParagraph) => true,
+                (Some(Element::Line(previous)), Element::Line(line), BlankLinePolicy::Transition) => {
+                    previous.is_dialogue() != line.is_dialogue()
+                }
+                (Some(_), _, BlankLinePolicy::EveryParagraph) => margin == 0,
+                _ => false,
+            };
+            if needs_empty_line && !lines.last().is_some_and(Element::is_empty_line) {
+                lines.push(Element::Line(Line::new("")));
+            }
+            empty_run = 0;
+            lines.push(element);
+        }
+        lines
+    }
+
+    /// Elements of every section, separated by headings if this chapter has preface or afterword
+    ///
+    /// Empty lines next to scene breaks are dropped if scene breaks have their own margin.
+    fn into_lines(self, format: Format) -> Vec<Element> {
+        let has_margin = self.scene_breaks.margin > 0;
+        let elements: Vec<Element> = if self.has_sections() {
+            let mut lines = Vec::new();
+            for (section, chapter) in self.into_sections() {
+                if !lines.is_empty() {
+                    lines.push(Element::Line(Line::new("")));
+                }
+                lines.push(Element::Line(Line::unindented(&syntax::escape(
+                    &section.heading_as(format),
+                ))));
+                lines.extend(chapter.lines.into_iter().map(|(_, element)| element));
+            }
+            lines
+        } else {
+            self.lines.into_iter().map(|(_, element)| element).collect()
+        };
+        let mut lines: Vec<Element> = Vec::new();
+        for element in elements {
+            match element {
+                Element::Break(_) if has_margin => {
+                    while lines.last().is_some_and(Element::is_empty_line) {
+                        lines.pop();
+                    }
+                    lines.push(element);
+                }
+                _ if has_margin && element.is_empty_line() && matches!(lines.last(), Some(Element::Break(_))) => (),
+                _ => lines.push(element),
+            }
+        }
+        lines
+    }
+}
+
+/// Lines of source text which are not commented out
+pub(crate) struct Body<'a> {
+    /// Lines with line index counted from 0 and the number of characters of a block comment before the line
+    pub(crate) lines: Vec<(usize, usize, &'a str)>,
+    /// Opening lines of unterminated block comments with line index counted from 0
+    pub(crate) unterminated: Vec<(usize, &'a str, BlockComment)>,
+}
+
+impl<'a> Body<'a> {
+    /// Strip comment lines and block comments from source text
+    ///
+    /// A block comment closes at the first closing mark, and text after it is kept as a line(`/* メモ */ 続き`).
+    /// An unterminated block comment strips only its opening line, so the rest of the text is kept.
+    pub(crate) fn strip(text: &'a str, rules: &SourceRules) -> Self {
+        let source: Vec<&str> = text.split_terminator('\n').collect();
+        let mut body = Self {
+            lines: Vec::new(),
+            unterminated: Vec::new(),
+        };
+        let mut index = 0;
+        while index < source.len() {
+            let line = source[index];
+            match BlockComment::opened_by(line) {
+                Some(block) => {
+                    let closing = block.closed_in_opening(line).map(|end| (index, end)).or_else(|| {
+                        source[index + 1..]
+                            .iter()
+                            .enumerate()
+                            .find_map(|(length, line)| block.closed_at(line).map(|end| (index + length + 1, end)))
+                    });
+                    match closing {
+                        Some((closed, end)) => {
+                            index = closed;
+                            let rest = &source[closed][end..];
+                            if !rest.trim().is_empty() && !rules.is_comment(rest) {
+                                body.lines.push((closed, source[closed][..end].chars().count(), rest));
+                            }
+                        }
+                        None => body.unterminated.push((index, line, block)),
+                    }
+                }
+                None if rules.is_comment(line) => (),
+                None => body.lines.push((index, 0, line)),
+            }
+            index += 1;
+        }
+        body
     }
 }
+
 #[cfg(test)]
 mod tests {
-    use super::Chapter;
+    use super::{Chapter, Section};
+    use crate::entities::scene_break::SceneBreakStyle;
+    use crate::format::{BlankLinePolicy, Format, Options};
 
     #[test]
     fn get() {
@@ -81,7 +803,201 @@ mod tests {
 // コメント行";
         let expected = "　我が輩は猫である。名前はまだない。
 　どこで｜生まれた《・・・・》のかとんと｜見当《けんとう》がつかぬ。";
-        let chapter = Chapter::new(&source);
+        let chapter = Chapter::new(source);
         assert_eq!(chapter.get(), expected);
     }
+
+    #[test]
+    fn get_as_kakuyomu() {
+        let source = "我が輩は猫である。名前はまだない。
+どこで[生まれた:.]のかとんと[見当:けんとう]がつかぬ。
+// コメント行";
+        let expected = "　我が輩は猫である。名前はまだない。
+　どこで《《生まれた》》のかとんと｜見当《けんとう》がつかぬ。";
+        let chapter = Chapter::new(source);
+        assert_eq!(chapter.get_as(Format::Kakuyomu), expected);
+    }
+
+    #[test]
+    fn get_as_html() {
+        let source = "我が輩は<猫>である。
+// コメント行
+「名前は[まだ:.]ない」";
+        let expected = "<body>
+<p>　我が輩は&lt;猫&gt;である。</p>
+<p>「名前は<em class=\"kenten\">まだ</em>ない」</p>
+</body>";
+        let chapter = Chapter::new(source);
+        let html = chapter.get_as(Format::Html);
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains(expected));
+        assert!(!html.contains("writing-mode"));
+    }
+
+    #[test]
+    fn get_as_latex() {
+        let source = "我が輩は猫である。
+// コメント行
+「名前は[まだ:.]ない」";
+        let expected = "\\noindent　我が輩は猫である。
+
+\\noindent「名前は\\kenten{まだ}ない」";
+        let chapter = Chapter::new(source);
+        assert_eq!(chapter.get_as(Format::Latex), expected);
+    }
+
+    #[test]
+    fn diagnostics_should_skip_comment_lines() {
+        let source = "// [東京都:とう.きょう]
+[猫.犬:ね.こ.だ]";
+        let diagnostics = Chapter::new(source).diagnostics();
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!((diagnostics[0].line, diagnostics[0].column), (2, 1));
+    }
+
+    #[test]
+    fn new_should_strip_block_comments() {
+        let source = "我が輩は猫である。
+/* 別案
+吾輩は犬である。
+
+*/
+/// begin
+　/* 構想メモ
+/// end
+名前はまだない。
+/* 一行の注記 */";
+        let chapter = Chapter::new(source);
+        assert!(chapter.diagnostics().is_empty());
+        assert_eq!(chapter.get(), "　我が輩は猫である。\n　名前はまだない。");
+    }
+
+    #[test]
+    fn new_should_keep_text_after_closing_block_comment() {
+        let chapter = Chapter::new("/* メモ */ 続き\n/* 別案\n吾輩は犬である。*/ [東.京:とう.きょう.と]");
+        assert_eq!(
+            chapter.diagnostics()[0].to_string(),
+            "3:12: Ruby segment mismatch error: 2 parent segments for 3 ruby segments"
+        );
+        assert_eq!(chapter.get(), "　続き\n　｜東京《とうきょうと》");
+    }
+
+    #[test]
+    fn diagnostics_should_report_unterminated_block_comment() {
+        let chapter = Chapter::new("我が輩は猫である。\n　/* 別案\n名前は[まだ:.]ない。");
+        assert_eq!(
+            chapter.diagnostics()[0].to_string(),
+            "2:2: Unterminated block comment error: Block comment is not closed by */"
+        );
+        assert_eq!(chapter.get(), "　我が輩は猫である。\n　名前は｜まだ《・・》ない。");
+    }
+
+    #[test]
+    fn new_should_split_sections_in_order() {
+        let source = "@afterword\n後書き\n\n@preface\n\n前書き\n@body\n本文";
+        let chapter = Chapter::new(source);
+        assert!(chapter.has_sections());
+        assert_eq!(
+            chapter.get(),
+            "【まえがき】\n　前書き\n\n【本文】\n　本文\n\n【あとがき】\n　後書き"
+        );
+    }
+
+    #[test]
+    fn get_as_should_render_pixiv_chapter_headings() {
+        let chapter = Chapter::new("@preface\n前書き\n@body\n本文");
+        assert_eq!(
+            chapter.get_as(Format::Pixiv),
+            "[chapter:まえがき]\n　前書き\n\n[chapter:本文]\n　本文"
+        );
+    }
+
+    #[test]
+    fn into_sections_should_have_empty_body_without_body_lines() {
+        let sections = Chapter::new("@preface\n前書き").into_sections();
+        let names: Vec<Section> = sections.iter().map(|(section, _)| *section).collect();
+        assert_eq!(names, vec![Section::Preface, Section::Body]);
+    }
+
+    #[test]
+    fn new_should_not_split_escaped_marker() {
+        let chapter = Chapter::new("\\@preface\n本文");
+        assert!(!chapter.has_sections());
+        assert_eq!(chapter.get(), "　@preface\n　本文");
+    }
+
+    #[test]
+    fn new_should_not_indent_scene_break() {
+        let style = SceneBreakStyle {
+            margin: 1,
+            ..SceneBreakStyle::default()
+        };
+        let chapter = Chapter::new("猫。\n\n＊\n\n\n犬。").with_scene_breaks(style);
+        let html = chapter.get_as(Format::Html);
+        assert!(html.contains("<p>　猫。</p>\n<p class=\"scene-break\">＊</p>\n<p>　犬。</p>"));
+    }
+
+    #[test]
+    fn with_scene_breaks_should_recognize_blank_run() {
+        let style = SceneBreakStyle {
+            blank_run: Some(2),
+            padding: 1,
+            margin: 0,
+            ..SceneBreakStyle::default()
+        };
+        let chapter = Chapter::new("猫。\n\n犬。\n\n\n\n鳥。").with_scene_breaks(style);
+        assert_eq!(chapter.get(), "　猫。\n\n　犬。\n　◇\n　鳥。");
+    }
+
+    #[test]
+    fn with_scene_breaks_should_restore_markers_which_are_not_recognized() {
+        let style = SceneBreakStyle {
+            markers: Vec::new(),
+            ..SceneBreakStyle::default()
+        };
+        let chapter = Chapter::new("猫。\n†\n犬。").with_scene_breaks(style);
+        assert_eq!(chapter.get(), "　猫。\n　†\n　犬。");
+    }
+
+    #[test]
+    fn new_should_render_page_break() {
+        let chapter = Chapter::new("猫。\n@newpage\n犬。");
+        assert_eq!(chapter.get_as(Format::Aozora), "　猫。\n［＃改ページ］\n　犬。");
+    }
+
+    #[test]
+    fn get_with_should_insert_empty_line_between_every_paragraph() {
+        let options = Options {
+            blank_lines: BlankLinePolicy::EveryParagraph,
+            ..Options::default()
+        };
+        let chapter = Chapter::new("猫。\n「犬」\n\n鳥。\n＊\n魚。");
+        assert_eq!(
+            chapter.get_with(Format::Narou, &options),
+            "　猫。\n\n「犬」\n\n　鳥。\n\n＊\n\n　魚。"
+        );
+    }
+
+    #[test]
+    fn get_with_should_insert_empty_line_at_transition() {
+        let options = Options {
+            blank_lines: BlankLinePolicy::Transition,
+            ..Options::default()
+        };
+        let chapter = Chapter::new("猫。\n犬。\n「鳥」\n「魚」\n虫。");
+        assert_eq!(
+            chapter.get_with(Format::Narou, &options),
+            "　猫。\n　犬。\n\n「鳥」\n「魚」\n\n　虫。"
+        );
+    }
+
+    #[test]
+    fn get_with_should_collapse_empty_lines() {
+        let options = Options {
+            blank_lines: BlankLinePolicy::Collapse(1),
+            ..Options::default()
+        };
+        let chapter = Chapter::new("猫。\n\n\n\n犬。\n\n鳥。");
+        assert_eq!(chapter.get_with(Format::Narou, &options), "　猫。\n\n　犬。\n\n　鳥。");
+    }
 }
@@ -0,0 +1,293 @@
+use crate::entities::syntax::Node;
+use crate::errors::GlossaryError;
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+
+/// Glossary of terms and their readings, which are added as ruby automatically
+///
+/// A longer term is matched first, and an explicit ruby in source text is kept as it is.
+#[derive(Default)]
+pub struct Glossary {
+    terms: Vec<(String, String)>,
+}
+
+/// Occurrences of a term to add ruby
+///
+/// * `Every` - every occurrence
+/// * `First` - only the first occurrence per chapter(episode)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum GlossaryMode {
+    #[default]
+    Every,
+    First,
+}
+
+impl Glossary {
+    /// Constructor of empty glossary
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use naromat::entities::glossary::Glossary;
+    ///
+    /// let mut glossary = Glossary::new();
+    /// glossary.insert("騎蜥", "きせき");
+    /// ```
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a term and its reading
+    pub fn insert(&mut self, term: &str, reading: &str) {
+        self.terms.retain(|(known, _)| known != term);
+        self.terms.push((term.to_string(), reading.to_string()));
+        self.terms
+            .sort_by_key(|(term, _)| std::cmp::Reverse(term.chars().count()));
+    }
+
+    /// Add terms of another glossary, whose readings take precedence
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use naromat::entities::glossary::Glossary;
+    ///
+    /// let mut glossary = Glossary::new();
+    /// glossary.insert("騎蜥", "きせき");
+    /// let mut other = Glossary::new();
+    /// other.insert("騎蜥", "きとかげ");
+    /// other.insert("竜", "りゅう");
+    /// glossary.merge(other);
+    /// assert_eq!(glossary.len(), 2);
+    /// ```
+    pub fn merge(&mut self, other: Glossary) {
+        for (term, reading) in other.terms {
+            self.insert(&term, &reading);
+        }
+    }
+
+    /// Number of terms
+    pub fn len(&self) -> usize {
+        self.terms.len()
+    }
+
+    /// Return true if glossary has no term
+    pub fn is_empty(&self) -> bool {
+        self.terms.is_empty()
+    }
+
+    /// Load a glossary file
+    ///
+    /// A file which has `toml` extension is read as TOML, otherwise as TSV.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use naromat::entities::glossary::Glossary;
+    ///
+    /// let glossary = Glossary::load("./path/to/glossary.tsv").unwrap();
+    /// ```
+    pub fn load(path: &str) -> Result<Self, GlossaryError> {
+        let text = fs::read_to_string(path).map_err(GlossaryError::CannotRead)?;
+        match Path::new(path).extension() {
+            Some(extension) if extension == "toml" => Self::from_toml(&text),
+            _ => Self::from_tsv(&text),
+        }
+    }
+
+    /// Parse TSV which has a term and its reading on each line
+    ///
+    /// Empty lines and lines which start with `#` are ignored.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use naromat::entities::glossary::Glossary;
+    ///
+    /// let glossary = Glossary::from_tsv("# 地名\n連邦首都\tケイレア\n騎蜥\tきせき\n").unwrap();
+    /// assert_eq!(glossary.len(), 2);
+    /// ```
+    pub fn from_tsv(text: &str) -> Result<Self, GlossaryError> {
+        let mut glossary = Self::new();
+        for (index, line) in text.lines().enumerate() {
+            if line.trim().is_empty() || line.starts_with('#') {
+                continue;
+            }
+            match line.split_once('\t') {
+                Some((term, reading)) if !term.trim().is_empty() && !reading.trim().is_empty() => {
+                    glossary.insert(term.trim(), reading.trim())
+                }
+                _ => return Err(GlossaryError::InvalidLine(index + 1)),
+            }
+        }
+        Ok(glossary)
+    }
+
+    /// Parse TOML which has terms as keys and readings as string values
+    ///
+    /// Terms which are not ASCII must be quoted.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use naromat::entities::glossary::Glossary;
+    ///
+    /// let glossary = Glossary::from_toml("\"連邦首都\" = \"ケイレア\"\n\"騎蜥\" = \"きせき\"\n").unwrap();
+    /// assert_eq!(glossary.len(), 2);
+    /// ```
+    pub fn from_toml(text: &str) -> Result<Self, GlossaryError> {
+        let value = text.parse::<toml::Value>().map_err(GlossaryError::InvalidToml)?;
+        let mut glossary = Self::new();
+        for (term, reading) in value.as_table().into_iter().flatten() {
+            match reading.as_str() {
+                Some(reading) if !reading.is_empty() => glossary.insert(term, reading),
+                _ => return Err(GlossaryError::InvalidReading(term.clone())),
+            }
+        }
+        Ok(glossary)
+    }
+
+    /// Add ruby to terms in text nodes
+    ///
+    /// Terms which have been used are recorded to `used`, and an explicit ruby of a term
+    /// is regarded as its occurrence.
+    /// Ruby added by glossary has the span of text node which the term is found in.
+    pub(crate) fn annotate(&self, nodes: Vec<Node>, mode: GlossaryMode, used: &mut HashSet<String>) -> Vec<Node> {
+        let mut annotated = Vec::new();
+        for node in nodes {
+            match node {
+                Node::Text { text, span } => annotated.append(&mut self.annotate_text(&text, span, mode, used)),
+                Node::Ruby { ref parent, .. } => {
+                    used.insert(parent.clone());
+                    annotated.push(node);
+                }
+                Node::SplitRuby { ref parent, .. } => {
+                    used.insert(parent.concat());
+                    annotated.push(node);
+                }
+                node => annotated.push(node),
+            }
+        }
+        annotated
+    }
+
+    /// Split a text to text nodes and ruby nodes of terms
+    fn annotate_text(
+        &self,
+        text: &str,
+        span: std::ops::Range<usize>,
+        mode: GlossaryMode,
+        used: &mut HashSet<String>,
+    ) -> Vec<Node> {
+        let mut nodes = Vec::new();
+        let mut plain = String::new();
+        let mut rest = text;
+        while let Some(character) = rest.chars().next() {
+            let term = self.terms.iter().find(|(term, _)| rest.starts_with(term.as_str()));
+            match term {
+                Some((term, reading)) => {
+                    if mode == GlossaryMode::First && used.contains(term) {
+                        plain.push_str(term);
+                    } else {
+                        if !plain.is_empty() {
+                            nodes.push(Node::Text {
+                                text: std::mem::take(&mut plain),
+                                span: span.clone(),
+                            });
+                        }
+                        nodes.push(Node::Ruby {
+                            parent: term.clone(),
+                            ruby: reading.clone(),
+                            span: span.clone(),
+                        });
+                        used.insert(term.clone());
+                    }
+                    rest = &rest[term.len()..];
+                }
+                None => {
+                    plain.push(character);
+                    rest = &rest[character.len_utf8()..];
+                }
+            }
+        }
+        if !plain.is_empty() {
+            nodes.push(Node::Text { text: plain, span });
+        }
+        nodes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entities::syntax::{parse, render};
+    use crate::format::Format;
+
+    fn glossary() -> Glossary {
+        let mut glossary = Glossary::new();
+        glossary.insert("連邦", "れんぽう");
+        glossary.insert("連邦首都", "ケイレア");
+        glossary.insert("騎蜥", "きせき");
+        glossary
+    }
+
+    fn annotate(text: &str, mode: GlossaryMode) -> String {
+        let nodes = glossary().annotate(parse(text), mode, &mut HashSet::new());
+        render(&nodes, Format::Narou)
+    }
+
+    #[test]
+    fn annotate_should_add_ruby_to_every_occurrence() {
+        assert_eq!(
+            annotate("騎蜥と騎蜥", GlossaryMode::Every),
+            "｜騎蜥《きせき》と｜騎蜥《きせき》"
+        );
+    }
+
+    #[test]
+    fn annotate_should_add_ruby_to_first_occurrence() {
+        assert_eq!(annotate("騎蜥と騎蜥", GlossaryMode::First), "｜騎蜥《きせき》と騎蜥");
+    }
+
+    #[test]
+    fn annotate_should_match_longer_term_first() {
+        assert_eq!(
+            annotate("連邦首都の連邦", GlossaryMode::Every),
+            "｜連邦首都《ケイレア》の｜連邦《れんぽう》"
+        );
+    }
+
+    #[test]
+    fn annotate_should_keep_explicit_ruby() {
+        assert_eq!(
+            annotate("[騎蜥:ドラゴン]と騎蜥", GlossaryMode::First),
+            "｜騎蜥《ドラゴン》と騎蜥"
+        );
+    }
+
+    #[test]
+    fn annotate_should_not_add_ruby_in_comment() {
+        assert_eq!(annotate("猫[#騎蜥]", GlossaryMode::Every), "猫");
+    }
+
+    #[test]
+    fn insert_should_replace_reading_of_known_term() {
+        let mut glossary = glossary();
+        glossary.insert("騎蜥", "ドラゴン");
+        assert_eq!(glossary.len(), 3);
+    }
+
+    #[test]
+    fn from_tsv_should_return_error_with_line_number() {
+        match Glossary::from_tsv("騎蜥\tきせき\n連邦首都 ケイレア") {
+            Err(GlossaryError::InvalidLine(line)) => assert_eq!(line, 2),
+            _ => panic!("invalid line should be reported"),
+        }
+    }
+
+    #[test]
+    fn from_toml_should_return_error_when_reading_is_not_string() {
+        assert!(Glossary::from_toml("\"騎蜥\" = 1").is_err());
+    }
+}
@@ -1,5 +1,13 @@
+use crate::entities::chapter::Section;
+use crate::entities::glossary::{Glossary, GlossaryMode};
+use crate::entities::punctuation::PunctuationRules;
+use crate::entities::scene_break::SceneBreak;
 use crate::entities::sentence::Sentence;
+use crate::entities::syntax::{KentenStyle, Node};
+use crate::entities::width::WidthRules;
+use crate::format::{docx, Format};
 use regex::Regex;
+use std::collections::HashSet;
 
 /// Structure of novel line
 ///
@@ -7,9 +15,98 @@ use regex::Regex;
 /// * Starts after breakline or chapter head
 /// * End with breakline
 pub struct Line {
+    header: String,
+    /// 2 byte whitespaces which the author wrote at line head
+    indent: String,
     elements: Vec<Sentence>,
 }
 
+/// Rules of indentation at line head
+///
+/// A line is indented with the indent of the first rule whose head the line starts with,
+/// or with `indent` if no rule matches. An empty indent suppresses indentation.
+///
+/// # Example
+///
+/// ```
+/// use naromat::entities::line::IndentRules;
+///
+/// let rules = IndentRules::default();
+/// assert_eq!(rules.header("", "我が輩は猫である。"), "　");
+/// assert_eq!(rules.header("", "「我が輩は猫である」"), "");
+/// assert_eq!(rules.header("　　", "我が輩は猫である。"), "　　");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IndentRules {
+    /// Indent of a paragraph
+    pub indent: String,
+    /// Pairs of line head and indent of lines which start with it
+    pub heads: Vec<(String, String)>,
+    /// Keep 2 byte whitespaces which the author wrote at line head instead of applying rules
+    pub preserve: bool,
+}
+
+impl Default for IndentRules {
+    fn default() -> Self {
+        Self {
+            indent: "　".to_string(),
+            heads: ["「", "『", "（"]
+                .iter()
+                .map(|head| (head.to_string(), String::new()))
+                .collect(),
+            preserve: true,
+        }
+    }
+}
+
+/// Rules of how lines of source text are read
+///
+/// # Example
+///
+/// ```
+/// use naromat::entities::line::SourceRules;
+///
+/// let rules = SourceRules { comment: "#".to_string() };
+/// assert!(rules.is_comment("　# メモ"));
+/// assert!(!rules.is_comment("// 本文"));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourceRules {
+    /// Prefix of a comment line, or an empty string for no comment line
+    pub comment: String,
+}
+
+impl Default for SourceRules {
+    fn default() -> Self {
+        Self {
+            comment: "//".to_string(),
+        }
+    }
+}
+
+impl SourceRules {
+    /// Return true if a line is a comment line
+    pub fn is_comment(&self, text: &str) -> bool {
+        !self.comment.is_empty() && text.trim().starts_with(self.comment.as_str())
+    }
+}
+
+impl IndentRules {
+    /// Whitespace to insert to head of a line which has designated author's indent and text
+    pub fn header(&self, indent: &str, text: &str) -> String {
+        if text.is_empty() {
+            return String::new();
+        }
+        if self.preserve && !indent.is_empty() {
+            return indent.to_string();
+        }
+        match self.heads.iter().find(|(head, _)| text.starts_with(head.as_str())) {
+            Some((_, header)) => header.clone(),
+            None => self.indent.clone(),
+        }
+    }
+}
+
 /// Implementation of novel line structure
 impl Line {
     /// Constructor
@@ -25,6 +122,34 @@ impl Line {
         Self::format(text)
     }
 
+    /// Constructor of line indented with designated characters
+    ///
+    /// Indent of 0 character is same as `Line::new`, and other indents are kept as the author's indent.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use naromat::entities::line::Line;
+    ///
+    /// let line = Line::indented("「我が輩は猫である」", 2);
+    /// assert_eq!(line.get(), "　　「我が輩は猫である」");
+    /// ```
+    pub fn indented(text: &str, indent: usize) -> Self {
+        let mut line = Self::format(text);
+        if indent > 0 {
+            line.indent = "　".repeat(indent);
+            line.apply_indent_rules(&IndentRules::default());
+        }
+        line
+    }
+
+    /// Constructor of line which is not indented
+    pub(crate) fn unindented(text: &str) -> Self {
+        let mut line = Self::format(text);
+        line.header.clear();
+        line
+    }
+
     /// Print formatted line
     ///
     /// # Example
@@ -52,25 +177,122 @@ impl Line {
     /// ```
     ///
     pub fn get(self) -> String {
-        self.elements.into_iter().map(|sentence| sentence.get()).collect()
+        self.get_as(Format::Narou)
     }
 
-    /// Format line
-    fn format(text: &str) -> Self {
-        let line = Self::add_header_space(text.trim());
-        let line = Self::split(&line)
+    /// Get string of line formatted to designated format
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use naromat::entities::line::Line;
+    /// use naromat::format::Format;
+    /// let line = Line::new("我が[輩:.]は[猫:ねこ]である。");
+    /// assert_eq!(line.get_as(Format::Kakuyomu), "　我が《《輩》》は｜猫《ねこ》である。");
+    /// ```
+    ///
+    pub fn get_as(self, format: Format) -> String {
+        let sentences: String = self
+            .elements
             .into_iter()
-            .map(|sentence| Sentence::new(&sentence))
+            .map(|sentence| sentence.get_as(format))
             .collect();
-        Self { elements: line }
+        if format == Format::Docx {
+            return docx::paragraph(&sentences, self.header.matches('　').count());
+        }
+        let line = self.header.to_string() + &sentences;
+        match format {
+            Format::Html | Format::Epub if line.is_empty() => "<p><br /></p>".to_string(),
+            Format::Html | Format::Epub => format!("<p>{}</p>", line),
+            Format::Latex if line.is_empty() => "\\noindent\\mbox{}".to_string(),
+            Format::Latex => format!("\\noindent{}", line),
+            _ => line,
+        }
+    }
+
+    /// Return true if this line is dialogue which starts with `「`
+    pub(crate) fn is_dialogue(&self) -> bool {
+        let head = self.elements.first().and_then(|sentence| sentence.nodes().first());
+        matches!(head, Some(Node::Text { text, .. }) if Self::is_speech(text))
+    }
+
+    /// 2 byte whitespaces which the author wrote at line head
+    pub(crate) fn indent(&self) -> &str {
+        &self.indent
     }
 
-    /// Insert 2 byte whitespace to line head
-    fn add_header_space(text: &str) -> String {
-        if Self::is_speech(text) {
-            return " ".to_string() + text;
+    /// Text of this line if it has no markup
+    pub(crate) fn plain_text(&self) -> Option<String> {
+        self.elements.iter().map(Sentence::plain_text).collect()
+    }
+
+    /// Add ruby of glossary terms to every sentence
+    pub(crate) fn apply_glossary(&mut self, glossary: &Glossary, mode: GlossaryMode, used: &mut HashSet<String>) {
+        for sentence in &mut self.elements {
+            sentence.apply_glossary(glossary, mode, used);
+        }
+    }
+
+    /// Use designated style for kenten which has no explicit style
+    pub(crate) fn apply_kenten_style(&mut self, style: KentenStyle) {
+        for sentence in &mut self.elements {
+            sentence.apply_kenten_style(style);
+        }
+    }
+
+    /// Normalize punctuation of every sentence
+    pub(crate) fn apply_punctuation(&mut self, rules: &PunctuationRules) {
+        for sentence in &mut self.elements {
+            sentence.apply_punctuation(rules);
+        }
+    }
+
+    /// Normalize width of characters of every sentence
+    pub(crate) fn apply_width(&mut self, rules: &WidthRules) {
+        for sentence in &mut self.elements {
+            sentence.apply_width(rules);
+        }
+    }
+
+    /// Use designated whitespace after exclamation in every sentence
+    pub(crate) fn apply_exclamation_space(&mut self, space: &str) {
+        for sentence in &mut self.elements {
+            sentence.apply_exclamation_space(space);
         }
-        "　".to_string() + text
+    }
+
+    /// Indent line head with designated rules
+    pub(crate) fn apply_indent_rules(&mut self, rules: &IndentRules) {
+        self.header = rules.header(&self.indent, &self.head_text());
+    }
+
+    /// Format line
+    fn format(text: &str) -> Self {
+        let head = &text[..text.len() - text.trim_start().len()];
+        let text = text.trim();
+        let mut line = Self {
+            header: String::new(),
+            indent: head.chars().filter(|character| *character == '　').collect(),
+            elements: Self::split(text).into_iter().map(Sentence::new).collect(),
+        };
+        line.apply_indent_rules(&IndentRules::default());
+        line
+    }
+
+    /// Text of line head without markup, which is matched with indent rules
+    fn head_text(&self) -> String {
+        let nodes = self.elements.iter().flat_map(|sentence| sentence.nodes());
+        nodes
+            .filter_map(|node| match node {
+                Node::Text { text, .. }
+                | Node::Exclamation { text, .. }
+                | Node::Kenten { text, .. }
+                | Node::TateChuYoko { text, .. } => Some(text.clone()),
+                Node::Ruby { parent, .. } => Some(parent.clone()),
+                Node::SplitRuby { parent, .. } => Some(parent.concat()),
+                Node::Comment { .. } => None,
+            })
+            .collect()
     }
 
     /// Split line to sentences
@@ -86,30 +308,214 @@ impl Line {
     }
 
     pub fn is_comment(text: &str) -> bool {
-        let line_head: String = text.trim().chars().take(2).collect();
-        line_head == "//"
+        SourceRules::default().is_comment(text)
+    }
+
+    /// Escape `//`, `/*` or markers(`@body`, `@newpage`) at line head so that the line is not a comment or a marker
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use naromat::entities::line::Line;
+    ///
+    /// let text = Line::escape_comment_head("　// 吾輩は猫である");
+    /// assert_eq!(text, r"　\// 吾輩は猫である");
+    /// assert!(!Line::is_comment(&text));
+    /// assert_eq!(Line::new(&text).get(), "　// 吾輩は猫である");
+    /// assert_eq!(Line::escape_comment_head("/* 猫 */"), r"\/* 猫 */");
+    /// assert_eq!(Line::escape_comment_head("@body"), r"\@body");
+    /// ```
+    pub fn escape_comment_head(text: &str) -> String {
+        let is_marker = Section::from_marker(text).is_some() || SceneBreak::is_page_marker(text);
+        if !Self::is_comment(text) && BlockComment::opened_by(text).is_none() && !is_marker {
+            return text.to_string();
+        }
+        let head = text.len() - text.trim_start().len();
+        format!("{}\\{}", &text[..head], &text[head..])
+    }
+}
+
+/// Kind of block comment which spans lines
+///
+/// * `Slash` - from a line which starts with `/*` to a line which ends with `*/`
+/// * `Marker` - from a line `/// begin` to a line `/// end`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlockComment {
+    Slash,
+    Marker,
+}
+
+impl BlockComment {
+    /// Kind of block comment which the line opens
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use naromat::entities::line::BlockComment;
+    ///
+    /// assert_eq!(BlockComment::opened_by("　/* 別案"), Some(BlockComment::Slash));
+    /// assert_eq!(BlockComment::opened_by("/// begin"), Some(BlockComment::Marker));
+    /// assert_eq!(BlockComment::opened_by("// 別案"), None);
+    /// ```
+    pub fn opened_by(text: &str) -> Option<Self> {
+        let text = text.trim();
+        if text.starts_with("/*") {
+            Some(BlockComment::Slash)
+        } else if text == "/// begin" {
+            Some(BlockComment::Marker)
+        } else {
+            None
+        }
+    }
+
+    /// Byte position after the first mark which closes this kind of block comment in the line
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use naromat::entities::line::BlockComment;
+    ///
+    /// assert_eq!(BlockComment::Slash.closed_at("メモ */ 続き"), Some(9));
+    /// assert_eq!(BlockComment::Marker.closed_at("/// end"), Some(7));
+    /// assert_eq!(BlockComment::Marker.closed_at("/// end 続き"), None);
+    /// ```
+    pub fn closed_at(self, text: &str) -> Option<usize> {
+        match self {
+            BlockComment::Slash => text
+                .find(self.closing())
+                .map(|position| position + self.closing().len()),
+            BlockComment::Marker if text.trim() == self.closing() => Some(text.len()),
+            BlockComment::Marker => None,
+        }
+    }
+
+    /// Byte position after the mark which closes the block comment in its opening line(`/* 別案 */`)
+    pub fn closed_in_opening(self, text: &str) -> Option<usize> {
+        match self {
+            BlockComment::Slash => {
+                let start = text.find("/*")? + 2;
+                self.closed_at(&text[start..]).map(|end| start + end)
+            }
+            BlockComment::Marker => None,
+        }
+    }
+
+    /// Mark which closes this kind of block comment
+    pub fn closing(self) -> &'static str {
+        match self {
+            BlockComment::Slash => "*/",
+            BlockComment::Marker => "/// end",
+        }
     }
 }
+
 #[cfg(test)]
 mod tests {
-    use super::Line;
+    use super::{IndentRules, Line};
+    use crate::format::docx;
+    use crate::format::Format;
 
     #[test]
     fn get() {
         let source = "我が[輩:.]は[猫:ねこ]である。どこで生まれたかとんと見当がつかぬ。";
         let expected = "　我が｜輩《・》は｜猫《ねこ》である。どこで生まれたかとんと見当がつかぬ。";
-        let line = Line::new(&source);
+        let line = Line::new(source);
         assert_eq!(line.get(), expected);
     }
 
+    #[test]
+    fn get_as_kakuyomu() {
+        let source = "我が[輩:.]は[猫:ねこ]である。どこで生まれたかとんと見当がつかぬ。";
+        let expected = "　我が《《輩》》は｜猫《ねこ》である。どこで生まれたかとんと見当がつかぬ。";
+        let line = Line::new(source);
+        assert_eq!(line.get_as(Format::Kakuyomu), expected);
+    }
+
+    #[test]
+    fn get_as_html() {
+        let source = "我が[輩:.]は[猫:ねこ]である。";
+        let expected = "<p>　我が<em class=\"kenten\">輩</em>は<ruby>猫<rt>ねこ</rt></ruby>である。</p>";
+        let line = Line::new(source);
+        assert_eq!(line.get_as(Format::Html), expected);
+    }
+
+    #[test]
+    fn get_as_html_empty() {
+        assert_eq!(Line::new("").get_as(Format::Html), "<p><br /></p>");
+    }
+
+    #[test]
+    fn get_as_latex() {
+        assert_eq!(
+            Line::new("我が[猫:ねこ]").get_as(Format::Latex),
+            "\\noindent　我が\\ruby{猫}{ねこ}"
+        );
+        assert_eq!(Line::new("「猫」").get_as(Format::Latex), "\\noindent「猫」");
+        assert_eq!(Line::new("").get_as(Format::Latex), "\\noindent\\mbox{}");
+    }
+
+    #[test]
+    fn get_as_docx() {
+        let indented = format!(
+            "<w:p><w:pPr><w:ind w:firstLineChars=\"100\"/></w:pPr>{}</w:p>",
+            docx::run("我が猫")
+        );
+        assert_eq!(Line::new("　我が猫").get_as(Format::Docx), indented);
+        assert_eq!(
+            Line::new("「猫」").get_as(Format::Docx),
+            format!("<w:p>{}</w:p>", docx::run("「猫」"))
+        );
+        assert_eq!(Line::new("").get_as(Format::Docx), "<w:p/>");
+    }
+
     #[test]
     fn get_min() {
         let source = "我";
         let expected = "　我";
-        let line = Line::new(&source);
+        let line = Line::new(source);
         assert_eq!(line.get(), expected);
     }
 
+    #[test]
+    fn new_should_preserve_indent_of_author() {
+        assert_eq!(Line::new("　　我が猫").get(), "　　我が猫");
+        assert_eq!(Line::new("　「猫」").get(), "　「猫」");
+        assert_eq!(Line::new(" 我が猫").get(), "　我が猫");
+    }
+
+    #[test]
+    fn new_should_not_indent_brackets() {
+        assert_eq!(Line::new("『猫』").get(), "『猫』");
+        assert_eq!(Line::new("（猫）").get(), "（猫）");
+        assert_eq!(Line::new("――猫").get(), "　――猫");
+    }
+
+    #[test]
+    fn apply_indent_rules_should_match_head_of_markup() {
+        let rules = IndentRules {
+            indent: "  ".to_string(),
+            heads: vec![("「猫".to_string(), String::new())],
+            preserve: false,
+        };
+        let mut line = Line::new("　「[猫:ねこ]だ」");
+        line.apply_indent_rules(&rules);
+        assert_eq!(line.get(), "「｜猫《ねこ》だ」");
+        let mut line = Line::new("　[猫:ねこ]だ");
+        line.apply_indent_rules(&rules);
+        assert_eq!(line.get(), "  ｜猫《ねこ》だ");
+    }
+
+    #[test]
+    fn apply_indent_rules_should_match_head_across_nodes() {
+        let rules = IndentRules {
+            heads: vec![("猫犬鳥".to_string(), String::new())],
+            ..IndentRules::default()
+        };
+        let mut line = Line::new("[猫:ねこ][犬:.]鳥");
+        line.apply_indent_rules(&rules);
+        assert_eq!(line.get(), "｜猫《ねこ》｜犬《・》鳥");
+    }
+
     #[test]
     fn is_comment_should_return_false_when_body_string() {
         assert!(!Line::is_comment("吾輩は猫である"));
@@ -135,6 +541,11 @@ mod tests {
         assert!(Line::is_comment("  // 吾輩は猫である"));
     }
 
+    #[test]
+    fn is_comment_should_return_false_when_escaped_comment_string() {
+        assert!(!Line::is_comment(r"\// 吾輩は猫である"));
+    }
+
     #[test]
     fn is_comment_should_return_true_when_multi_full_spaced_comment_string() {
         assert!(Line::is_comment("　　// 吾輩は猫である"));
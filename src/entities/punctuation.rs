@@ -0,0 +1,215 @@
+use crate::entities::syntax::{self, Node};
+use regex::{Captures, Regex};
+
+/// Rules to normalize punctuation along Japanese web novel conventions
+///
+/// * `ellipsis` - `...` and `・・・` become `……`
+/// * `dash` - `--`, and `ーー` which is not a long vowel mark after kana, become `――`
+/// * `even_marks` - runs of `…` and `―` have even number of marks
+/// * `exclamation` - `!` and `?` become `！` and `？` unless they are a part of `!?` or `?!`
+/// * `double_exclamation` - `！！` becomes `‼`
+/// * `period_before_bracket` - `。` before `」` is removed
+///
+/// Every rule is enabled by default.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PunctuationRules {
+    pub ellipsis: bool,
+    pub dash: bool,
+    pub even_marks: bool,
+    pub exclamation: bool,
+    pub double_exclamation: bool,
+    pub period_before_bracket: bool,
+}
+
+impl Default for PunctuationRules {
+    fn default() -> Self {
+        Self {
+            ellipsis: true,
+            dash: true,
+            even_marks: true,
+            exclamation: true,
+            double_exclamation: true,
+            period_before_bracket: true,
+        }
+    }
+}
+
+impl PunctuationRules {
+    /// Rules which are all disabled
+    pub fn none() -> Self {
+        Self {
+            ellipsis: false,
+            dash: false,
+            even_marks: false,
+            exclamation: false,
+            double_exclamation: false,
+            period_before_bracket: false,
+        }
+    }
+
+    /// Normalize punctuation of plain text
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use naromat::entities::punctuation::PunctuationRules;
+    ///
+    /// let rules = PunctuationRules::default();
+    /// assert_eq!(rules.normalize("「待って...!!」"), "「待って……‼」");
+    /// assert_eq!(rules.normalize("そうか…ーーえ!?"), "そうか……――え!?");
+    /// let rules = PunctuationRules {
+    ///     period_before_bracket: false,
+    ///     ..PunctuationRules::default()
+    /// };
+    /// assert_eq!(rules.normalize("「猫だ。」"), "「猫だ。」");
+    /// ```
+    pub fn normalize(&self, text: &str) -> String {
+        let mut text = text.to_string();
+        if self.ellipsis {
+            text = replace(&text, r"\.{3,}|・{3,}", |run| "……".repeat(run.chars().count() / 3));
+        }
+        if self.dash {
+            text = replace(&text, r"-{2,}", |run| "―".repeat(run.chars().count()));
+            text = Regex::new(r"(^|[^ぁ-ゖァ-ヺｦ-ﾝ])(ー{2,})")
+                .unwrap()
+                .replace_all(&text, |captures: &Captures| {
+                    format!("{}{}", &captures[1], "―".repeat(captures[2].chars().count()))
+                })
+                .into_owned();
+        }
+        if self.even_marks {
+            text = replace(&text, r"…+|―+", |run| {
+                let count = run.chars().count();
+                run.chars().take(1).collect::<String>().repeat(count + count % 2)
+            });
+        }
+        if self.exclamation {
+            text = replace(&text, r"!\?|\?!|!|\?", |mark| match mark {
+                "!" => "！".to_string(),
+                "?" => "？".to_string(),
+                mark => mark.to_string(),
+            });
+        }
+        if self.double_exclamation {
+            text = text.replace("！！", "‼");
+        }
+        if self.period_before_bracket {
+            text = replace(&text, r"。+」", |_| "」".to_string());
+        }
+        text
+    }
+
+    /// Normalize punctuation in text nodes and exclamation nodes
+    ///
+    /// Consecutive text and exclamation nodes are normalized together, so that marks across them are matched,
+    /// and the normalized nodes have the span of all of them.
+    pub(crate) fn apply(&self, nodes: Vec<Node>) -> Vec<Node> {
+        let mut normalized = Vec::new();
+        let mut run: Option<(String, std::ops::Range<usize>)> = None;
+        for node in nodes {
+            match node {
+                Node::Text { text, span } | Node::Exclamation { text, span } => {
+                    run = Some(match run.take() {
+                        Some((plain, start)) => (plain + &text, start.start..span.end),
+                        None => (text, span),
+                    });
+                }
+                node => {
+                    if let Some((plain, span)) = run.take() {
+                        normalized.append(&mut syntax::split_exclamations(&self.normalize(&plain), span));
+                    }
+                    normalized.push(node);
+                }
+            }
+        }
+        if let Some((plain, span)) = run {
+            normalized.append(&mut syntax::split_exclamations(&self.normalize(&plain), span));
+        }
+        normalized
+    }
+}
+
+/// Replace every match of a pattern with a result of the function
+fn replace(text: &str, pattern: &str, replacer: impl Fn(&str) -> String) -> String {
+    Regex::new(pattern)
+        .unwrap()
+        .replace_all(text, |captures: &Captures| replacer(&captures[0]))
+        .into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalize_should_convert_ellipsis_per_three_dots() {
+        let rules = PunctuationRules::default();
+        assert_eq!(rules.normalize("猫・・・"), "猫……");
+        assert_eq!(rules.normalize("猫......"), "猫…………");
+        assert_eq!(rules.normalize("1.5と2..3"), "1.5と2..3");
+    }
+
+    #[test]
+    fn normalize_should_make_marks_even() {
+        let rules = PunctuationRules::default();
+        assert_eq!(rules.normalize("猫…"), "猫……");
+        assert_eq!(rules.normalize("―――猫"), "――――猫");
+        assert_eq!(rules.normalize("猫--犬"), "猫――犬");
+    }
+
+    #[test]
+    fn normalize_should_convert_exclamation() {
+        let rules = PunctuationRules::default();
+        assert_eq!(rules.normalize("猫!犬?鳥!?"), "猫！犬？鳥!?");
+        assert_eq!(rules.normalize("猫！！！"), "猫‼！");
+    }
+
+    #[test]
+    fn normalize_should_apply_only_enabled_rules() {
+        let rules = PunctuationRules {
+            dash: true,
+            ..PunctuationRules::none()
+        };
+        assert_eq!(rules.normalize("猫ーーい…!"), "猫――い…!");
+    }
+
+    #[test]
+    fn normalize_should_keep_long_vowel_marks_after_kana() {
+        let rules = PunctuationRules::default();
+        assert_eq!(rules.normalize("すごーーい"), "すごーーい");
+        assert_eq!(rules.normalize("ハーーイ"), "ハーーイ");
+        assert_eq!(rules.normalize("ーーそうか"), "――そうか");
+    }
+
+    #[test]
+    fn apply_should_normalize_across_nodes() {
+        let rules = PunctuationRules::default();
+        let nodes = rules.apply(syntax::parse("[猫:ねこ]だ！！よ。」"));
+        assert_eq!(
+            syntax::render(&nodes, crate::format::Format::Narou),
+            "｜猫《ねこ》だ‼　よ」"
+        );
+    }
+
+    #[test]
+    fn apply_should_split_converted_exclamations() {
+        let rules = PunctuationRules::default();
+        assert_eq!(
+            rules.apply(syntax::parse("猫!犬")),
+            vec![
+                Node::Text {
+                    text: "猫".to_string(),
+                    span: 0..7
+                },
+                Node::Exclamation {
+                    text: "！".to_string(),
+                    span: 0..7
+                },
+                Node::Text {
+                    text: "犬".to_string(),
+                    span: 0..7
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,272 @@
+use crate::entities::chapter::Body;
+use crate::entities::line::SourceRules;
+use crate::entities::syntax::{self, Node};
+use crate::import::{aozora, Dialect};
+use std::collections::BTreeMap;
+
+/// Index of ruby collected from source texts
+///
+/// Comments are skipped, and each pair of parent and ruby has its occurrence count and files it appears in.
+/// A split ruby is collected as a pair of whole parent and whole ruby.
+#[derive(Default)]
+pub struct RubyIndex {
+    entries: BTreeMap<String, BTreeMap<String, Occurrence>>,
+}
+
+/// Occurrences of a pair of parent and ruby
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Occurrence {
+    /// Number of occurrences
+    pub count: usize,
+    /// Files which the pair appears in, in the order of first appearance
+    pub files: Vec<String>,
+}
+
+impl RubyIndex {
+    /// Constructor of empty index
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Collect ruby in naromat source text of a file
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use naromat::entities::ruby_index::RubyIndex;
+    ///
+    /// let mut index = RubyIndex::new();
+    /// index.collect("1.txt", "[古:いにしえ]の[古:いにしえ]\n// [古:ふる]");
+    /// index.collect("2.txt", "[古:ふる]い");
+    /// assert_eq!(index.occurrence("古", "いにしえ").unwrap().count, 2);
+    /// assert_eq!(index.conflicts(), vec!["古"]);
+    /// ```
+    pub fn collect(&mut self, file: &str, text: &str) {
+        self.collect_with(file, text, Dialect::Naromat, &SourceRules::default())
+    }
+
+    /// Collect ruby in source text of a file written in designated dialect, with designated rules
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use naromat::entities::line::SourceRules;
+    /// use naromat::entities::ruby_index::RubyIndex;
+    /// use naromat::import::Dialect;
+    ///
+    /// let rules = SourceRules { comment: "#".to_string() };
+    /// let mut index = RubyIndex::new();
+    /// index.collect_with("1.txt", "古《いにしえ》の\n# [古:ふる]", Dialect::Aozora, &rules);
+    /// assert_eq!(index.occurrence("古", "いにしえ").unwrap().count, 1);
+    /// assert!(index.occurrence("古", "ふる").is_none());
+    /// ```
+    pub fn collect_with(&mut self, file: &str, text: &str, dialect: Dialect, rules: &SourceRules) {
+        let lines: Vec<String> = match dialect {
+            Dialect::Naromat => Body::strip(text, rules)
+                .lines
+                .into_iter()
+                .map(|(_, _, line)| line.to_string())
+                .collect(),
+            Dialect::Aozora => aozora::import(text)
+                .into_iter()
+                .map(|(_, line)| line)
+                .filter(|line| !rules.is_comment(line))
+                .collect(),
+        };
+        for line in lines {
+            for node in syntax::parse(line.trim()) {
+                match node {
+                    Node::Ruby { parent, ruby, .. } => self.push(file, parent, ruby),
+                    Node::SplitRuby { parent, ruby, .. } => {
+                        let (parent, ruby) = syntax::whole_ruby(&parent, &ruby);
+                        self.push(file, parent, ruby)
+                    }
+                    _ => (),
+                }
+            }
+        }
+    }
+
+    /// Occurrences of a pair of parent and ruby
+    pub fn occurrence(&self, parent: &str, ruby: &str) -> Option<&Occurrence> {
+        self.entries.get(parent).and_then(|readings| readings.get(ruby))
+    }
+
+    /// Parents which appear with different rubies
+    pub fn conflicts(&self) -> Vec<&str> {
+        self.entries
+            .iter()
+            .filter(|(_, readings)| readings.len() > 1)
+            .map(|(parent, _)| parent.as_str())
+            .collect()
+    }
+
+    /// Readings of a parent, in the order of ruby
+    pub fn readings(&self, parent: &str) -> Vec<&str> {
+        self.entries
+            .get(parent)
+            .map(|readings| readings.keys().map(String::as_str).collect())
+            .unwrap_or_default()
+    }
+
+    /// Write the index as CSV
+    ///
+    /// Columns are parent, ruby, count, files separated by `;` and conflict flag.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use naromat::entities::ruby_index::RubyIndex;
+    ///
+    /// let mut index = RubyIndex::new();
+    /// index.collect("1.txt", "[古:いにしえ]");
+    /// assert_eq!(index.to_csv(), "parent,ruby,count,files,conflict\n古,いにしえ,1,1.txt,false\n");
+    /// ```
+    pub fn to_csv(&self) -> String {
+        let mut csv = "parent,ruby,count,files,conflict\n".to_string();
+        for (parent, ruby, occurrence, conflict) in self.rows() {
+            csv.push_str(&format!(
+                "{},{},{},{},{}\n",
+                csv_field(parent),
+                csv_field(ruby),
+                occurrence.count,
+                csv_field(&occurrence.files.join(";")),
+                conflict
+            ));
+        }
+        csv
+    }
+
+    /// Write the index as JSON array
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use naromat::entities::ruby_index::RubyIndex;
+    ///
+    /// let mut index = RubyIndex::new();
+    /// index.collect("1.txt", "[古:いにしえ]");
+    /// assert_eq!(
+    ///     index.to_json(),
+    ///     "[\n  {\"parent\": \"古\", \"ruby\": \"いにしえ\", \"count\": 1, \"files\": [\"1.txt\"], \"conflict\": false}\n]\n"
+    /// );
+    /// ```
+    pub fn to_json(&self) -> String {
+        let entries: Vec<String> = self
+            .rows()
+            .map(|(parent, ruby, occurrence, conflict)| {
+                let files: Vec<String> = occurrence.files.iter().map(|file| json_string(file)).collect();
+                format!(
+                    "  {{\"parent\": {}, \"ruby\": {}, \"count\": {}, \"files\": [{}], \"conflict\": {}}}",
+                    json_string(parent),
+                    json_string(ruby),
+                    occurrence.count,
+                    files.join(", "),
+                    conflict
+                )
+            })
+            .collect();
+        if entries.is_empty() {
+            return "[]\n".to_string();
+        }
+        format!("[\n{}\n]\n", entries.join(",\n"))
+    }
+
+    /// Record an occurrence
+    fn push(&mut self, file: &str, parent: String, ruby: String) {
+        let occurrence = self.entries.entry(parent).or_default().entry(ruby).or_default();
+        occurrence.count += 1;
+        if !occurrence.files.iter().any(|known| known == file) {
+            occurrence.files.push(file.to_string());
+        }
+    }
+
+    /// Rows of parent, ruby, occurrence and conflict flag
+    fn rows(&self) -> impl Iterator<Item = (&str, &str, &Occurrence, bool)> {
+        self.entries.iter().flat_map(|(parent, readings)| {
+            readings
+                .iter()
+                .map(move |(ruby, occurrence)| (parent.as_str(), ruby.as_str(), occurrence, readings.len() > 1))
+        })
+    }
+}
+
+/// Quote a CSV field if it has special characters
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        return format!("\"{}\"", field.replace('"', "\"\""));
+    }
+    field.to_string()
+}
+
+/// Quote a JSON string
+fn json_string(text: &str) -> String {
+    let mut quoted = "\"".to_string();
+    for character in text.chars() {
+        match character {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            character if (character as u32) < 0x20 => quoted.push_str(&format!("\\u{:04x}", character as u32)),
+            character => quoted.push(character),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn collect_should_count_occurrences_per_file() {
+        let mut index = RubyIndex::new();
+        index.collect("1.txt", "[騎蜥:きせき]と[騎蜥:きせき]");
+        index.collect("2.txt", "[騎蜥:きせき]");
+        assert_eq!(
+            index.occurrence("騎蜥", "きせき"),
+            Some(&Occurrence {
+                count: 3,
+                files: vec!["1.txt".to_string(), "2.txt".to_string()]
+            })
+        );
+    }
+
+    #[test]
+    fn collect_should_join_split_ruby() {
+        let mut index = RubyIndex::new();
+        index.collect("1.txt", "[東.京.都:とう.きょう.と]");
+        assert_eq!(index.readings("東京都"), vec!["とうきょうと"]);
+    }
+
+    #[test]
+    fn collect_should_ignore_kenten_and_comments() {
+        let mut index = RubyIndex::new();
+        index.collect("1.txt", "[猫:.][#犬:いぬ]\n/*\n[犬:いぬ]\n*/");
+        assert!(index.conflicts().is_empty());
+        assert_eq!(index.to_json(), "[]\n");
+    }
+
+    #[test]
+    fn to_csv_should_flag_conflicts() {
+        let mut index = RubyIndex::new();
+        index.collect("1.txt", "[古:ふる][古:いにしえ]");
+        assert_eq!(
+            index.to_csv(),
+            "parent,ruby,count,files,conflict\n古,いにしえ,1,1.txt,true\n古,ふる,1,1.txt,true\n"
+        );
+    }
+
+    #[test]
+    fn csv_field_should_quote_special_characters() {
+        assert_eq!(csv_field("a,\"b\""), "\"a,\"\"b\"\"\"");
+    }
+
+    #[test]
+    fn json_string_should_escape_special_characters() {
+        assert_eq!(json_string("a\"\\\n"), "\"a\\\"\\\\\\n\"");
+    }
+}
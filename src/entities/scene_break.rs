@@ -0,0 +1,222 @@
+use crate::format::{docx, html, latex, Format};
+
+/// Break of scenes or pages in a chapter
+///
+/// * `Marker` - a line of a scene break marker(`†`, `＊`, `◇◆◇` and so on) with the author's indent
+/// * `Blank` - a run of empty lines
+/// * `Page` - a page break(`@newpage` or `@改ページ`)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SceneBreak {
+    Marker(String),
+    Blank(usize),
+    Page,
+}
+
+/// Style of scene breaks, how they are recognized and rendered
+///
+/// Padding and margin are used by plain text formats, and the other formats center breaks by themselves.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SceneBreakStyle {
+    /// Lines which are recognized as scene breaks
+    pub markers: Vec<String>,
+    /// Number of consecutive empty lines which is recognized as a scene break, or `None` to keep empty lines
+    pub blank_run: Option<usize>,
+    /// Marker to render every scene break with, or `None` to keep the marker in source
+    pub marker: Option<String>,
+    /// Number of 2 byte whitespaces before a marker, in addition to the author's indent
+    pub padding: usize,
+    /// Number of empty lines before and after a scene break
+    pub margin: usize,
+    /// Render every scene break as `［＃改ページ］` in Aozora, or as an indented marker if false
+    pub aozora_page_break: bool,
+}
+
+impl Default for SceneBreakStyle {
+    fn default() -> Self {
+        Self {
+            markers: ["†", "＊", "＊＊＊", "◇", "◆", "◇◆◇", "◆◇◆"]
+                .iter()
+                .map(|marker| marker.to_string())
+                .collect(),
+            blank_run: None,
+            marker: None,
+            padding: 0,
+            margin: 0,
+            aozora_page_break: true,
+        }
+    }
+}
+
+/// Marker of a scene break which has no marker in source
+const DEFAULT_MARKER: &str = "◇";
+
+impl SceneBreak {
+    /// Return true if the line is a page break marker
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use naromat::entities::scene_break::SceneBreak;
+    ///
+    /// assert!(SceneBreak::is_page_marker(" @newpage"));
+    /// assert!(!SceneBreak::is_page_marker(r"\@newpage"));
+    /// ```
+    pub fn is_page_marker(text: &str) -> bool {
+        matches!(text.trim(), "@newpage" | "@改ページ")
+    }
+
+    /// Get string of scene break formatted to designated format
+    ///
+    /// * Narou, Kakuyomu : a padded marker with empty lines around it, also for a page break
+    /// * Aozora : `［＃改ページ］`, or a marker indented by `［＃N字下げ］` unless `aozora_page_break`
+    /// * Pixiv : a padded marker, and `[newpage]`
+    /// * Html, Epub : `<p class="scene-break">` and `<hr class="page-break" />`
+    /// * Latex : `\scenebreak{◇}` and `\clearpage`
+    /// * Docx : a centered paragraph and a page break
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use naromat::entities::scene_break::{SceneBreak, SceneBreakStyle};
+    /// use naromat::format::Format;
+    ///
+    /// let style = SceneBreakStyle {
+    ///     padding: 2,
+    ///     margin: 1,
+    ///     ..SceneBreakStyle::default()
+    /// };
+    /// let scene_break = SceneBreak::Marker("†".to_string());
+    /// assert_eq!(scene_break.get_as(Format::Narou, &style), "\n　　†\n");
+    /// assert_eq!(scene_break.get_as(Format::Html, &style), "<p class=\"scene-break\">†</p>");
+    /// ```
+    pub fn get_as(&self, format: Format, style: &SceneBreakStyle) -> String {
+        let (indent, marker) = match self {
+            SceneBreak::Marker(marker) => marker.split_at(marker.len() - marker.trim_start_matches('　').len()),
+            _ => ("", DEFAULT_MARKER),
+        };
+        let marker = style.marker.as_deref().unwrap_or(marker);
+        let padding = style.padding + indent.chars().count();
+        match (format, self) {
+            (Format::Aozora, SceneBreak::Page) => "［＃改ページ］".to_string(),
+            (Format::Aozora, _) if style.aozora_page_break => "［＃改ページ］".to_string(),
+            (Format::Aozora, _) if padding == 0 => Self::with_margin(marker, style.margin),
+            (Format::Aozora, _) => {
+                let indent = format!("［＃{}字下げ］{}", full_width_number(padding), marker);
+                Self::with_margin(&indent, style.margin)
+            }
+            (Format::Pixiv, SceneBreak::Page) => "[newpage]".to_string(),
+            (Format::Html, SceneBreak::Page) | (Format::Epub, SceneBreak::Page) => {
+                "<hr class=\"page-break\" />".to_string()
+            }
+            (Format::Html, _) | (Format::Epub, _) => format!("<p class=\"scene-break\">{}</p>", html::escape(marker)),
+            (Format::Latex, SceneBreak::Page) => "\\clearpage".to_string(),
+            (Format::Latex, _) => format!("\\scenebreak{{{}}}", latex::escape(marker)),
+            (Format::Docx, SceneBreak::Page) => docx::page_break(),
+            (Format::Docx, _) => docx::centered_paragraph(&docx::run(&html::escape(marker))),
+            _ => {
+                let padded = format!("{}{}", "　".repeat(padding), marker);
+                Self::with_margin(&padded, style.margin)
+            }
+        }
+    }
+
+    /// Text of scene break in source, which is restored when the break is no longer recognized
+    pub(crate) fn source_lines(&self) -> Vec<String> {
+        match self {
+            SceneBreak::Marker(marker) => vec![marker.clone()],
+            SceneBreak::Blank(count) => vec![String::new(); *count],
+            SceneBreak::Page => vec!["@newpage".to_string()],
+        }
+    }
+
+    /// Surround a line with empty lines
+    fn with_margin(line: &str, margin: usize) -> String {
+        let empty = "\n".repeat(margin);
+        format!("{}{}{}", empty, line, empty)
+    }
+}
+
+/// Number written in full width digits
+fn full_width_number(number: usize) -> String {
+    number
+        .to_string()
+        .chars()
+        .filter_map(|digit| digit.to_digit(10))
+        .filter_map(|digit| char::from_u32('０' as u32 + digit))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn get_as_should_render_blank_run_with_default_marker() {
+        let style = SceneBreakStyle {
+            padding: 1,
+            margin: 0,
+            ..SceneBreakStyle::default()
+        };
+        assert_eq!(SceneBreak::Blank(3).get_as(Format::Kakuyomu, &style), "　◇");
+    }
+
+    #[test]
+    fn get_as_should_keep_indent_of_author() {
+        let style = SceneBreakStyle::default();
+        let scene_break = SceneBreak::Marker("　†".to_string());
+        assert_eq!(scene_break.get_as(Format::Narou, &style), "　†");
+        assert_eq!(
+            scene_break.get_as(Format::Html, &style),
+            "<p class=\"scene-break\">†</p>"
+        );
+    }
+
+    #[test]
+    fn get_as_should_replace_marker_with_style_marker() {
+        let style = SceneBreakStyle {
+            marker: Some("◇◆◇".to_string()),
+            ..SceneBreakStyle::default()
+        };
+        assert_eq!(
+            SceneBreak::Marker("†".to_string()).get_as(Format::Latex, &style),
+            "\\scenebreak{◇◆◇}"
+        );
+    }
+
+    #[test]
+    fn get_as_should_render_aozora_page_break_by_default() {
+        let style = SceneBreakStyle::default();
+        assert_eq!(
+            SceneBreak::Marker("＊".to_string()).get_as(Format::Aozora, &style),
+            "［＃改ページ］"
+        );
+        assert_eq!(SceneBreak::Blank(3).get_as(Format::Aozora, &style), "［＃改ページ］");
+    }
+
+    #[test]
+    fn get_as_should_render_aozora_indent_and_page_break() {
+        let style = SceneBreakStyle {
+            padding: 12,
+            margin: 0,
+            aozora_page_break: false,
+            ..SceneBreakStyle::default()
+        };
+        assert_eq!(
+            SceneBreak::Marker("＊".to_string()).get_as(Format::Aozora, &style),
+            "［＃１２字下げ］＊"
+        );
+        assert_eq!(SceneBreak::Page.get_as(Format::Aozora, &style), "［＃改ページ］");
+    }
+
+    #[test]
+    fn get_as_should_render_page_break_per_format() {
+        let style = SceneBreakStyle::default();
+        assert_eq!(SceneBreak::Page.get_as(Format::Pixiv, &style), "[newpage]");
+        assert_eq!(
+            SceneBreak::Page.get_as(Format::Epub, &style),
+            "<hr class=\"page-break\" />"
+        );
+        assert_eq!(SceneBreak::Page.get_as(Format::Latex, &style), "\\clearpage");
+        assert_eq!(SceneBreak::Page.get_as(Format::Docx, &style), docx::page_break());
+    }
+}
@@ -1,5 +1,9 @@
-use regex::Captures;
-use regex::Regex;
+use crate::entities::glossary::{Glossary, GlossaryMode};
+use crate::entities::punctuation::PunctuationRules;
+use crate::entities::syntax::{self, KentenStyle, Node};
+use crate::entities::width::WidthRules;
+use crate::format::Format;
+use std::collections::HashSet;
 
 /// Structure of novel sentence.
 ///
@@ -14,7 +18,9 @@ use regex::Regex;
 ///     * '!?'
 ///
 pub struct Sentence {
-    elements: String,
+    nodes: Vec<Node>,
+    /// Whitespace inserted after exclamation
+    exclamation_space: String,
 }
 
 /// Implementation for novel sentence structure
@@ -30,7 +36,8 @@ impl Sentence {
     /// ```
     pub fn new(sentence: &str) -> Self {
         Self {
-            elements: sentence.trim_end().to_string(),
+            nodes: syntax::parse(sentence.trim_end()),
+            exclamation_space: "　".to_string(),
         }
     }
 
@@ -45,7 +52,7 @@ impl Sentence {
     /// sentence.print()
     /// ```
     pub fn print(self) {
-        println!("{}", self.format().elements)
+        println!("{}", self.format(Format::Narou))
     }
 
     /// Get string of formatted sentence
@@ -59,119 +66,269 @@ impl Sentence {
     /// ```
     ///
     pub fn get(self) -> String {
-        self.format().elements
+        self.get_as(Format::Narou)
     }
 
-    /// Format sentence
-    fn format(&self) -> Self {
-        self.add_space_after_exclamation()
-            .convert_kenten()
-            .convert_ruby()
-            .drop_comment()
-    }
-
-    /// Insert 2 byte whitespace to after of exclamation.
-    fn add_space_after_exclamation(&self) -> Self {
-        let exclamations = Regex::new(r"(!\?|\?!|[！？])").unwrap();
-        let sentence = exclamations.replace_all(&self.elements, "$1　").to_string();
-        let error_tails = Regex::new(r"　([」)）])").unwrap();
-        let sentence = error_tails.replace_all(&sentence, "$1").to_string();
-        Self::new(&sentence)
-    }
-
-    /// Convert kenten(圏点) format to Narou format.
-    fn convert_kenten(&self) -> Self {
-        let kenten = Regex::new(r"\[([^]]*?):\.\]").unwrap();
-        let sentence = kenten
-            .replace_all(&self.elements, |caps: &Captures| {
-                format!("｜{}《{}》", &caps[1], "・".to_string().repeat(caps[1].chars().count()))
+    /// Get string of sentence formatted to designated format
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use naromat::entities::sentence::Sentence;
+    /// use naromat::format::Format;
+    /// let sentence = Sentence::new("我が[輩:.]は[猫:ねこ]である");
+    /// assert_eq!(sentence.get_as(Format::Kakuyomu), "我が《《輩》》は｜猫《ねこ》である");
+    /// ```
+    ///
+    pub fn get_as(self, format: Format) -> String {
+        self.format(format)
+    }
+
+    /// Syntax tree of this sentence
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use naromat::entities::sentence::Sentence;
+    /// use naromat::entities::syntax::Node;
+    ///
+    /// let sentence = Sentence::new("我が[輩:.]は猫である");
+    /// assert_eq!(
+    ///     sentence.nodes()[1],
+    ///     Node::Kenten { text: "輩".to_string(), style: None, span: 6..13 }
+    /// );
+    /// ```
+    pub fn nodes(&self) -> &[Node] {
+        &self.nodes
+    }
+
+    /// Add ruby of glossary terms
+    pub(crate) fn apply_glossary(&mut self, glossary: &Glossary, mode: GlossaryMode, used: &mut HashSet<String>) {
+        let nodes = std::mem::take(&mut self.nodes);
+        self.nodes = glossary.annotate(nodes, mode, used);
+    }
+
+    /// Text of this sentence if it has no markup
+    pub(crate) fn plain_text(&self) -> Option<String> {
+        self.nodes
+            .iter()
+            .map(|node| match node {
+                Node::Text { text, .. } | Node::Exclamation { text, .. } | Node::TateChuYoko { text, .. } => {
+                    Some(text.as_str())
+                }
+                _ => None,
             })
-            .to_string();
-        Self::new(&sentence)
+            .collect()
+    }
+
+    /// Normalize punctuation with designated rules
+    pub(crate) fn apply_punctuation(&mut self, rules: &PunctuationRules) {
+        let nodes = std::mem::take(&mut self.nodes);
+        self.nodes = rules.apply(nodes);
+    }
+
+    /// Normalize width of characters with designated rules
+    pub(crate) fn apply_width(&mut self, rules: &WidthRules) {
+        let nodes = std::mem::take(&mut self.nodes);
+        self.nodes = rules.apply(nodes);
+    }
+
+    /// Use designated whitespace after exclamation
+    pub(crate) fn apply_exclamation_space(&mut self, space: &str) {
+        self.exclamation_space = space.to_string();
     }
 
-    /// Convert ruby(ルビ) format to Narou format
-    fn convert_ruby(&self) -> Self {
-        let ruby = Regex::new(r"\[(.*?):(.*?)\]").unwrap();
-        let sentence = ruby.replace_all(&self.elements, "｜$1《$2》").to_string();
-        Self::new(&sentence)
+    /// Use designated style for kenten which has no explicit style
+    pub(crate) fn apply_kenten_style(&mut self, default: KentenStyle) {
+        for node in &mut self.nodes {
+            if let Node::Kenten {
+                style: style @ None, ..
+            } = node
+            {
+                *style = Some(default);
+            }
+        }
     }
 
-    /**
-    Drop comment block from this sentence.
-    */
-    fn drop_comment(&self) -> Self {
-        let comment = Regex::new(r"\[#.*\]").unwrap();
-        let sentence = comment.replace(self.elements.as_str(), "").to_string();
-        Sentence::new(sentence.as_str())
+    /// Format sentence
+    fn format(&self, format: Format) -> String {
+        syntax::render_with(&self.nodes, format, &self.exclamation_space)
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::Sentence;
+    use crate::entities::syntax::KentenStyle;
+    use crate::format::docx;
+    use crate::format::Format;
 
     #[test]
     fn get() {
         let source = "私の[名前:なまえ]は！[田中:.]？太郎!?です";
         let expected = "私の｜名前《なまえ》は！　｜田中《・・》？　太郎!?　です";
-        let sentence = Sentence::new(&source);
+        let sentence = Sentence::new(source);
         assert_eq!(sentence.get(), expected);
     }
     #[test]
     fn format() {
         let source = "私の[名前:なまえ]は！[田中:.]？太郎!?です";
         let expected = "私の｜名前《なまえ》は！　｜田中《・・》？　太郎!?　です";
-        let sentence = Sentence::new(&source).format();
-        assert_eq!(sentence.elements, expected);
+        assert_eq!(Sentence::new(source).format(Format::Narou), expected);
     }
 
     #[test]
     fn format_min() {
         let source = "私";
         let expected = "私";
-        let sentence = Sentence::new(&source).format();
-        assert_eq!(sentence.elements, expected);
+        assert_eq!(Sentence::new(source).format(Format::Narou), expected);
     }
 
     #[test]
     fn add_space_after_exclamation() {
         let source = "私の名前は！田中？太郎!?です";
         let expected = "私の名前は！　田中？　太郎!?　です";
-        let sentence = Sentence::new(&source).add_space_after_exclamation();
-        assert_eq!(sentence.elements, expected);
+        assert_eq!(Sentence::new(source).get(), expected);
     }
 
     #[test]
     fn convert_ruby_should_convert_ruby() {
         let sut = Sentence::new("私の[名前:なまえ]は[太郎:たろう]です");
         let expected = "私の｜名前《なまえ》は｜太郎《たろう》です";
-        assert_eq!(sut.convert_ruby().elements, expected);
+        assert_eq!(sut.get(), expected);
     }
 
     #[test]
     fn convert_ruby_should_not_convert_broken_syntax_ruby() {
         let sut = Sentence::new("私の[名前:なまえは[太郎:たろうです");
         let expected = "私の[名前:なまえは[太郎:たろうです";
-        assert_eq!(sut.convert_ruby().elements, expected);
+        assert_eq!(sut.get(), expected);
     }
 
     #[test]
     fn convert_kenten_should_convert_kenten() {
         let sut = Sentence::new("私の[名前:.]は[たろう:.]です");
         let expected = "私の｜名前《・・》は｜たろう《・・・》です";
-        assert_eq!(sut.convert_kenten().elements, expected);
+        assert_eq!(sut.get(), expected);
+    }
+
+    #[test]
+    fn convert_kenten_should_convert_kenten_to_kakuyomu() {
+        let sut = Sentence::new("私の[名前:.]は[たろう:.]です");
+        let expected = "私の《《名前》》は《《たろう》》です";
+        assert_eq!(sut.get_as(Format::Kakuyomu), expected);
+    }
+
+    #[test]
+    fn get_as_kakuyomu() {
+        let source = "私の[名前:なまえ]は！[田中:.]？太郎!?です";
+        let expected = "私の｜名前《なまえ》は！　《《田中》》？　太郎!?　です";
+        assert_eq!(Sentence::new(source).get_as(Format::Kakuyomu), expected);
+    }
+
+    #[test]
+    fn convert_kenten_should_convert_kenten_to_aozora() {
+        let sut = Sentence::new("私の[名前:.]は[たろう:.]です");
+        let expected = "私の名前［＃「名前」に傍点］はたろう［＃「たろう」に傍点］です";
+        assert_eq!(sut.get_as(Format::Aozora), expected);
+    }
+
+    #[test]
+    fn get_as_aozora() {
+        let source = "私の[名前:なまえ]は[#仮名]！[田中:.]です";
+        let expected = "私の｜名前《なまえ》は！　田中［＃「田中」に傍点］です";
+        assert_eq!(Sentence::new(source).get_as(Format::Aozora), expected);
+    }
+
+    #[test]
+    fn convert_ruby_should_convert_ruby_to_pixiv() {
+        let sut = Sentence::new("私の[名前:なまえ]は[太郎:たろう]です");
+        let expected = "私の[[rb:名前 > なまえ]]は[[rb:太郎 > たろう]]です";
+        assert_eq!(sut.get_as(Format::Pixiv), expected);
+    }
+
+    #[test]
+    fn get_as_pixiv() {
+        let source = "私の[名前:なまえ]は！[田中:.]です";
+        let expected = "私の[[rb:名前 > なまえ]]は！　[[rb:田中 > ・・]]です";
+        assert_eq!(Sentence::new(source).get_as(Format::Pixiv), expected);
+    }
+
+    #[test]
+    fn get_as_html() {
+        let source = "<私>の[名前:なまえ]は！[田中&鈴木:.]です";
+        let expected = "&lt;私&gt;の<ruby>名前<rt>なまえ</rt></ruby>は！　<em class=\"kenten\">田中&amp;鈴木</em>です";
+        assert_eq!(Sentence::new(source).get_as(Format::Html), expected);
+    }
+
+    #[test]
+    fn get_as_latex() {
+        let source = "100%の[名前:なまえ]は[#仮]！[田中_1:.]です";
+        let expected = r"100\%の\ruby{名前}{なまえ}は！　\kenten{田中\_1}です";
+        assert_eq!(Sentence::new(source).get_as(Format::Latex), expected);
+    }
+
+    #[test]
+    fn get_as_docx() {
+        let source = "<私>の[名前:なまえ]は[田中:.]です";
+        let expected = format!(
+            "{}{}{}{}{}",
+            docx::run("&lt;私&gt;の"),
+            docx::ruby("名前", "なまえ"),
+            docx::run("は"),
+            docx::emphasis("田中"),
+            docx::run("です")
+        );
+        assert_eq!(Sentence::new(source).get_as(Format::Docx), expected);
+    }
+
+    #[test]
+    fn get_as_should_render_kenten_styles() {
+        let source = "[猫:.*][犬:.o][鳥:.●][魚:.]";
+        assert_eq!(Sentence::new(source).get(), "｜猫《﹅》｜犬《◦》｜鳥《●》｜魚《・》");
+        assert_eq!(
+            Sentence::new(source).get_as(Format::Html),
+            "<em class=\"kenten sesame\">猫</em><em class=\"kenten open-circle\">犬</em>\
+             <em class=\"kenten filled-circle\">鳥</em><em class=\"kenten\">魚</em>"
+        );
+        assert_eq!(
+            Sentence::new(source).get_as(Format::Aozora),
+            "猫［＃「猫」に傍点］犬［＃「犬」に白丸傍点］鳥［＃「鳥」に丸傍点］魚［＃「魚」に傍点］"
+        );
+        assert_eq!(
+            Sentence::new(source).get_as(Format::Kakuyomu),
+            "《《猫》》《《犬》》《《鳥》》《《魚》》"
+        );
+    }
+
+    #[test]
+    fn apply_kenten_style_should_change_kenten_without_explicit_style() {
+        let mut sentence = Sentence::new("[猫:.][犬:.・]");
+        sentence.apply_kenten_style(KentenStyle::Sesame);
+        assert_eq!(sentence.get(), "｜猫《﹅》｜犬《・》");
     }
 
     #[test]
     fn drop_comment_should_drop_comment() {
-        let sentence = Sentence::new("我が輩は猫[#犬にするか要検討]である").drop_comment();
-        assert_eq!(sentence.drop_comment().elements, "我が輩は猫である")
+        let sentence = Sentence::new("我が輩は猫[#犬にするか要検討]である");
+        assert_eq!(sentence.get(), "我が輩は猫である")
     }
 
     #[test]
     fn drop_comment_should_drop_empty_comment() {
-        let sentence = Sentence::new("我が輩は犬[#]である").drop_comment();
-        assert_eq!(sentence.drop_comment().elements, "我が輩は犬である")
+        let sentence = Sentence::new("我が輩は犬[#]である");
+        assert_eq!(sentence.get(), "我が輩は犬である")
+    }
+
+    #[test]
+    fn drop_comment_should_drop_every_comment_only() {
+        let sentence = Sentence::new("我が輩は[#仮]猫である。[#要検討]名前は[まだ:.]ない");
+        assert_eq!(sentence.get(), "我が輩は猫である。名前は｜まだ《・・》ない")
+    }
+
+    #[test]
+    fn convert_ruby_should_not_convert_across_brackets() {
+        let sut = Sentence::new("[注意]私の[名前:なまえ]");
+        assert_eq!(sut.get(), "[注意]私の｜名前《なまえ》")
     }
 }
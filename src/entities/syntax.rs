@@ -0,0 +1,944 @@
+use crate::errors::MarkupError;
+use crate::format::{docx, html, latex};
+use crate::format::{Format, RubyLimits};
+use std::ops::Range;
+
+/// Node of naromat markup syntax tree
+///
+/// Every node has a byte span in the parsed text.
+///
+/// * `Text` - plain text
+/// * `Ruby` - `[親:ルビ]`
+/// * `SplitRuby` - `[東京都:とう.きょう.と]`, `[東.京.都:とう.きょう.と]` or `[東京.都:とうきょう.と]`, ruby which is split
+///   by `.` to per-character ruby, or to per-segment ruby if the parent is also split by `.`
+/// * `Kenten` - `[強調:.]`, or `[強調:.*]`, `[強調:.o]` and `[強調:.●]` with designated style
+/// * `Comment` - `[#コメント]`
+/// * `Exclamation` - `！`, `？`, `‼`, `!?` and `?!`
+/// * `TateChuYoko` - digits set horizontally in vertical text, which is marked by width normalization
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Node {
+    Text {
+        text: String,
+        span: Range<usize>,
+    },
+    Ruby {
+        parent: String,
+        ruby: String,
+        span: Range<usize>,
+    },
+    SplitRuby {
+        parent: Vec<String>,
+        ruby: Vec<String>,
+        span: Range<usize>,
+    },
+    Kenten {
+        text: String,
+        /// Style of marks, or `None` to use the default style
+        style: Option<KentenStyle>,
+        span: Range<usize>,
+    },
+    Comment {
+        text: String,
+        span: Range<usize>,
+    },
+    Exclamation {
+        text: String,
+        span: Range<usize>,
+    },
+    TateChuYoko {
+        text: String,
+        span: Range<usize>,
+    },
+}
+
+/// Style of kenten(圏点) marks
+///
+/// * `Dot` - `・`, written as `[強調:.・]`(or `[強調:.]` by default)
+/// * `Sesame` - `﹅`, written as `[強調:.*]` or `[強調:.﹅]`
+/// * `OpenCircle` - `◦`, written as `[強調:.o]` or `[強調:.◦]`
+/// * `FilledCircle` - `●`, written as `[強調:.●]`
+///
+/// Marks are led by `.` so that ruby such as `[丸:o]` stays ruby.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum KentenStyle {
+    #[default]
+    Dot,
+    Sesame,
+    OpenCircle,
+    FilledCircle,
+}
+
+impl KentenStyle {
+    /// Character of a mark
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use naromat::entities::syntax::KentenStyle;
+    ///
+    /// assert_eq!(KentenStyle::Sesame.mark(), '﹅');
+    /// ```
+    pub fn mark(self) -> char {
+        match self {
+            KentenStyle::Dot => '・',
+            KentenStyle::Sesame => '﹅',
+            KentenStyle::OpenCircle => '◦',
+            KentenStyle::FilledCircle => '●',
+        }
+    }
+
+    /// Style designated by ruby of kenten markup
+    fn from_markup(ruby: &str) -> Option<Self> {
+        match ruby.strip_prefix('.')? {
+            "・" => Some(KentenStyle::Dot),
+            "*" | "﹅" => Some(KentenStyle::Sesame),
+            "o" | "◦" => Some(KentenStyle::OpenCircle),
+            "●" => Some(KentenStyle::FilledCircle),
+            _ => None,
+        }
+    }
+}
+
+impl Node {
+    /// Byte span of this node in the parsed text
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use naromat::entities::syntax::parse;
+    ///
+    /// let nodes = parse("我が[猫:ねこ]");
+    /// assert_eq!(nodes[1].span(), 6..18);
+    /// ```
+    pub fn span(&self) -> Range<usize> {
+        match self {
+            Node::Text { span, .. }
+            | Node::Ruby { span, .. }
+            | Node::SplitRuby { span, .. }
+            | Node::Kenten { span, .. }
+            | Node::Comment { span, .. }
+            | Node::Exclamation { span, .. }
+            | Node::TateChuYoko { span, .. } => span.clone(),
+        }
+    }
+}
+
+/// Parse naromat markup to syntax tree
+///
+/// A bracket which is not closed before next `[` is a plain text.
+///
+/// # Example
+///
+/// ```
+/// use naromat::entities::syntax::{parse, Node};
+///
+/// assert_eq!(
+///     parse("猫[#仮]！[犬:.]"),
+///     vec![
+///         Node::Text { text: "猫".to_string(), span: 0..3 },
+///         Node::Comment { text: "仮".to_string(), span: 3..9 },
+///         Node::Exclamation { text: "！".to_string(), span: 9..12 },
+///         Node::Kenten { text: "犬".to_string(), style: None, span: 12..19 },
+///     ]
+/// );
+/// ```
+pub fn parse(text: &str) -> Vec<Node> {
+    let mut nodes = Vec::new();
+    let mut plain = String::new();
+    let mut text_start = 0;
+    let mut position = 0;
+    while position < text.len() {
+        match markup(text, position).or_else(|| exclamation(text, position)) {
+            Some(node) => {
+                push_text(&mut nodes, &mut plain, text_start..position);
+                position = node.span().end;
+                text_start = position;
+                nodes.push(node);
+            }
+            None => {
+                let (character, length) = unescaped_char(&text[position..]);
+                plain.push(character);
+                position += length;
+            }
+        }
+    }
+    push_text(&mut nodes, &mut plain, text_start..text.len());
+    nodes
+}
+
+/// Escape characters which have special meaning in naromat markup
+///
+/// `\`, `[` and `]` are escaped by `\`.
+///
+/// # Example
+///
+/// ```
+/// use naromat::entities::syntax::{escape, parse, Node};
+///
+/// assert_eq!(escape("[HP:100]"), r"\[HP:100\]");
+/// assert_eq!(
+///     parse(&escape("[HP:100]")),
+///     vec![Node::Text { text: "[HP:100]".to_string(), span: 0..10 }]
+/// );
+/// ```
+pub fn escape(text: &str) -> String {
+    let mut escaped = String::new();
+    for character in text.chars() {
+        if matches!(character, '\\' | '[' | ']') {
+            escaped.push('\\');
+        }
+        escaped.push(character);
+    }
+    escaped
+}
+
+/// Render syntax tree to designated format
+///
+/// Comments are dropped, and 2 byte whitespace is inserted to after of exclamation
+/// unless the exclamation ends the text or is followed by closing bracket.
+///
+/// # Example
+///
+/// ```
+/// use naromat::entities::syntax::{parse, render, Node};
+/// use naromat::format::Format;
+///
+/// let nodes: Vec<Node> = parse("我が[輩:.]は[猫:ねこ]である！")
+///     .into_iter()
+///     .map(|node| match node {
+///         Node::Ruby { parent, span, .. } => Node::Ruby { parent, ruby: "いぬ".to_string(), span },
+///         node => node,
+///     })
+///     .collect();
+/// assert_eq!(render(&nodes, Format::Kakuyomu), "我が《《輩》》は｜猫《いぬ》である！");
+/// ```
+pub fn render(nodes: &[Node], format: Format) -> String {
+    render_with(nodes, format, "　")
+}
+
+/// Render syntax tree to designated format with designated whitespace after exclamation
+///
+/// # Example
+///
+/// ```
+/// use naromat::entities::syntax::{parse, render_with};
+/// use naromat::format::Format;
+///
+/// assert_eq!(render_with(&parse("猫！犬？"), Format::Narou, " "), "猫！ 犬？");
+/// assert_eq!(render_with(&parse("猫！犬？"), Format::Narou, ""), "猫！犬？");
+/// ```
+pub fn render_with(nodes: &[Node], format: Format, space: &str) -> String {
+    let mut rendered = String::new();
+    let mut text = String::new();
+    for (index, node) in nodes.iter().enumerate() {
+        match node {
+            Node::Text { text: plain, .. } => text.push_str(plain),
+            Node::Exclamation { text: exclamation, .. } => {
+                text.push_str(exclamation);
+                if needs_space_after(&nodes[index + 1..]) {
+                    text.push_str(space);
+                }
+            }
+            Node::Comment { .. } => {}
+            Node::TateChuYoko { text: digits, .. } => match format {
+                Format::Html | Format::Epub => {
+                    rendered.push_str(&render_text(&text, format));
+                    rendered.push_str(&format!("<span class=\"tcy\">{}</span>", html::escape(digits)));
+                    text.clear();
+                }
+                _ => text.push_str(digits),
+            },
+            Node::Ruby { parent, ruby, .. } => {
+                rendered.push_str(&render_text(&text, format));
+                rendered.push_str(&render_ruby(parent, ruby, format));
+                text.clear();
+            }
+            Node::SplitRuby { parent, ruby, .. } => {
+                rendered.push_str(&render_text(&text, format));
+                rendered.push_str(&render_split_ruby(parent, ruby, format));
+                text.clear();
+            }
+            Node::Kenten {
+                text: emphasized,
+                style,
+                ..
+            } => {
+                rendered.push_str(&render_text(&text, format));
+                rendered.push_str(&render_kenten(emphasized, style.unwrap_or_default(), format));
+                text.clear();
+            }
+        }
+    }
+    rendered.push_str(&render_text(text.trim_end(), format));
+    rendered
+}
+
+/// Return true if whitespace is needed after exclamation which is followed by designated nodes
+fn needs_space_after(following: &[Node]) -> bool {
+    match following.iter().find(|node| !matches!(node, Node::Comment { .. })) {
+        None => false,
+        Some(Node::Text { text, .. }) => !text.starts_with(['」', ')', '）']),
+        Some(_) => true,
+    }
+}
+
+/// Render plain text, escaping characters which have special meaning in designated format
+fn render_text(text: &str, format: Format) -> String {
+    match format {
+        Format::Html | Format::Epub => html::escape(text),
+        Format::Latex => latex::escape(text),
+        Format::Docx => docx::run(&html::escape(text)),
+        _ => text.to_string(),
+    }
+}
+
+/// Render kenten(圏点) to designated format
+///
+/// * Narou : `｜強調《・・》` with marks of the style
+/// * Kakuyomu : `《《強調》》` (kakuyomu has only sesame marks)
+/// * Aozora : `強調［＃「強調」に傍点］`, `丸傍点` or `白丸傍点`
+/// * Pixiv : `[[rb:強調 > ・・]]` (pixiv has no kenten, so it falls back to ruby with marks)
+/// * Html, Epub : `<em class="kenten">強調</em>` with a class of the style(`sesame`, `open-circle` or `filled-circle`)
+/// * Latex : `\kenten{強調}`, or ruby with marks for circles
+/// * Docx : a run with `w:em` emphasis mark
+fn render_kenten(text: &str, style: KentenStyle, format: Format) -> String {
+    let marks = style.mark().to_string().repeat(text.chars().count());
+    match (format, style) {
+        (Format::Narou, _) => format!("｜{}《{}》", text, marks),
+        (Format::Kakuyomu, _) => format!("《《{}》》", text),
+        (Format::Aozora, KentenStyle::Dot) | (Format::Aozora, KentenStyle::Sesame) => {
+            format!("{0}［＃「{0}」に傍点］", text)
+        }
+        (Format::Aozora, KentenStyle::OpenCircle) => format!("{0}［＃「{0}」に白丸傍点］", text),
+        (Format::Aozora, KentenStyle::FilledCircle) => format!("{0}［＃「{0}」に丸傍点］", text),
+        (Format::Pixiv, _) => render_ruby(text, &marks, format),
+        (Format::Html, _) | (Format::Epub, _) => match html::kenten_class(style) {
+            Some(class) => format!("<em class=\"kenten {}\">{}</em>", class, html::escape(text)),
+            None => format!("<em class=\"kenten\">{}</em>", html::escape(text)),
+        },
+        (Format::Latex, KentenStyle::Dot) | (Format::Latex, KentenStyle::Sesame) => {
+            format!("\\kenten{{{}}}", latex::escape(text))
+        }
+        (Format::Latex, _) => render_ruby(text, &marks, format),
+        (Format::Docx, _) => docx::emphasis_as(&html::escape(text), docx::emphasis_mark(style)),
+    }
+}
+
+/// Render ruby(ルビ) to designated format
+///
+/// Narou, Kakuyomu and Aozora all accept a ruby which starts with explicit `｜`,
+/// so the parent text of ruby is not restricted to kanji.
+///
+/// Pixiv uses `[[rb:親 > ルビ]]`, Html and Epub use `<ruby>` element, Latex uses `\ruby{親}{ルビ}`
+/// and Docx uses a run with native `w:ruby`.
+fn render_ruby(parent: &str, ruby: &str, format: Format) -> String {
+    match format {
+        Format::Narou | Format::Kakuyomu | Format::Aozora => format!("｜{}《{}》", parent, ruby),
+        Format::Pixiv => format!("[[rb:{} > {}]]", parent, ruby),
+        Format::Html | Format::Epub => format!("<ruby>{}<rt>{}</rt></ruby>", html::escape(parent), html::escape(ruby)),
+        Format::Latex => format!("\\ruby{{{}}}{{{}}}", latex::escape(parent), latex::escape(ruby)),
+        Format::Docx => docx::ruby(&html::escape(parent), &html::escape(ruby)),
+    }
+}
+
+/// Render split ruby as per-segment ruby
+///
+/// Ruby whose number of segments is different from parent is rendered as a group ruby.
+fn render_split_ruby(parent: &[String], ruby: &[String], format: Format) -> String {
+    if parent.len() != ruby.len() {
+        let (parent, ruby) = whole_ruby(parent, ruby);
+        return render_ruby(&parent, &ruby, format);
+    }
+    parent
+        .iter()
+        .zip(ruby)
+        .map(|(parent, ruby)| render_ruby(parent, ruby, format))
+        .collect()
+}
+
+/// Validate syntax tree
+///
+/// Return errors with byte span of the node.
+///
+/// # Example
+///
+/// ```
+/// use naromat::entities::syntax::{parse, validate};
+/// use naromat::errors::MarkupError;
+///
+/// assert_eq!(
+///     validate(&parse("[東.京.都:とう.きょう]")),
+///     vec![(0..30, MarkupError::RubySegmentMismatch { parent: 3, ruby: 2 })]
+/// );
+/// ```
+pub fn validate(nodes: &[Node]) -> Vec<(Range<usize>, MarkupError)> {
+    nodes
+        .iter()
+        .filter_map(|node| match node {
+            Node::SplitRuby { parent, ruby, span } if parent.len() != ruby.len() => Some((
+                span.clone(),
+                MarkupError::RubySegmentMismatch {
+                    parent: parent.len(),
+                    ruby: ruby.len(),
+                },
+            )),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Validate syntax tree against limits of designated format
+///
+/// In addition to `validate`, every ruby and kenten which is rendered as ruby are checked
+/// with `Format::ruby_limits`.
+///
+/// # Example
+///
+/// ```
+/// use naromat::entities::syntax::{parse, validate_for};
+/// use naromat::errors::MarkupError;
+/// use naromat::format::Format;
+///
+/// let nodes = parse("[寿限無寿限無五劫の擦り切れ:じゅげむ]");
+/// assert_eq!(
+///     validate_for(&nodes, Format::Narou),
+///     vec![(0..54, MarkupError::RubyParentTooLong { length: 13, limit: 10 })]
+/// );
+/// assert_eq!(validate_for(&nodes, Format::Kakuyomu), vec![]);
+/// ```
+pub fn validate_for(nodes: &[Node], format: Format) -> Vec<(Range<usize>, MarkupError)> {
+    let mut errors = validate(nodes);
+    let limits = match format.ruby_limits() {
+        Some(limits) => limits,
+        None => return errors,
+    };
+    for node in nodes {
+        let rubies = match node {
+            Node::Ruby { parent, ruby, .. } => vec![(parent.clone(), ruby.clone())],
+            Node::SplitRuby { parent, ruby, .. } if parent.len() == ruby.len() => {
+                parent.iter().cloned().zip(ruby.iter().cloned()).collect()
+            }
+            Node::SplitRuby { parent, ruby, .. } => vec![whole_ruby(parent, ruby)],
+            Node::Kenten { text, .. } if format == Format::Narou => vec![(text.clone(), String::new())],
+            _ => continue,
+        };
+        for (parent, ruby) in rubies {
+            for error in check_ruby(&parent, &ruby, limits) {
+                errors.push((node.span(), error));
+            }
+        }
+    }
+    errors
+}
+
+/// Check a pair of parent and ruby with limits
+fn check_ruby(parent: &str, ruby: &str, limits: RubyLimits) -> Vec<MarkupError> {
+    let mut errors = Vec::new();
+    let length = parent.chars().count();
+    if length > limits.parent {
+        errors.push(MarkupError::RubyParentTooLong {
+            length,
+            limit: limits.parent,
+        });
+    }
+    let length = ruby.chars().count();
+    if length > limits.ruby {
+        errors.push(MarkupError::RubyTooLong {
+            length,
+            limit: limits.ruby,
+        });
+    }
+    if let Some(character) = parent
+        .chars()
+        .chain(ruby.chars())
+        .find(|c| limits.disallowed.contains(c))
+    {
+        errors.push(MarkupError::DisallowedRubyCharacter(character));
+    }
+    errors
+}
+
+/// Split plain text to text nodes and exclamation nodes, which have the designated span
+pub(crate) fn split_exclamations(text: &str, span: Range<usize>) -> Vec<Node> {
+    let mut nodes = Vec::new();
+    let mut plain = String::new();
+    let mut position = 0;
+    while position < text.len() {
+        match exclamation(text, position) {
+            Some(Node::Exclamation { text: mark, .. }) => {
+                if !plain.is_empty() {
+                    nodes.push(Node::Text {
+                        text: std::mem::take(&mut plain),
+                        span: span.clone(),
+                    });
+                }
+                position += mark.len();
+                nodes.push(Node::Exclamation {
+                    text: mark,
+                    span: span.clone(),
+                });
+            }
+            _ => {
+                let character = text[position..].chars().next().unwrap();
+                plain.push(character);
+                position += character.len_utf8();
+            }
+        }
+    }
+    if !plain.is_empty() {
+        nodes.push(Node::Text { text: plain, span });
+    }
+    nodes
+}
+
+/// Push buffered plain text as a text node if the span is not empty
+fn push_text(nodes: &mut Vec<Node>, plain: &mut String, span: Range<usize>) {
+    if span.is_empty() {
+        return;
+    }
+    nodes.push(Node::Text {
+        text: std::mem::take(plain),
+        span,
+    });
+}
+
+/// Parse bracket markup(`[...]`) which starts at the position
+fn markup(text: &str, position: usize) -> Option<Node> {
+    let rest = text[position..].strip_prefix('[')?;
+    let end = find_unescaped(rest, &['[', ']'])?;
+    if !rest[end..].starts_with(']') {
+        return None;
+    }
+    let content = &rest[..end];
+    let span = position..position + end + 2;
+    if let Some(comment) = content.strip_prefix('#') {
+        return Some(Node::Comment {
+            text: unescape(comment),
+            span,
+        });
+    }
+    let colon = find_unescaped(content, &[':'])?;
+    match (&content[..colon], &content[colon + 1..]) {
+        ("", _) | (_, "") => None,
+        (parent, ".") => Some(Node::Kenten {
+            text: unescape(parent),
+            style: None,
+            span,
+        }),
+        (parent, ruby) if KentenStyle::from_markup(ruby).is_some() => Some(Node::Kenten {
+            text: unescape(parent),
+            style: KentenStyle::from_markup(ruby),
+            span,
+        }),
+        (parent, ruby) => {
+            let segments = split_unescaped(ruby, '.');
+            let parents = split_unescaped(parent, '.');
+            let characters: Vec<String> = unescape(parent).chars().map(String::from).collect();
+            let parent = match parents.len() {
+                _ if segments.len() < 2 || segments.contains(&"") => None,
+                1 if characters.len() == segments.len() => Some(characters),
+                1 => None,
+                _ => Some(parents.into_iter().map(unescape).collect()),
+            };
+            match parent {
+                Some(parent) => Some(Node::SplitRuby {
+                    parent,
+                    ruby: segments.into_iter().map(unescape).collect(),
+                    span,
+                }),
+                None => Some(Node::Ruby {
+                    parent: unescape(&content[..colon]),
+                    ruby: unescape(ruby),
+                    span,
+                }),
+            }
+        }
+    }
+}
+
+/// Parent and ruby of split ruby as a single ruby
+///
+/// Ruby which is not split in parent keeps `.` as written, and segments of split parent and ruby are joined.
+pub(crate) fn whole_ruby(parent: &[String], ruby: &[String]) -> (String, String) {
+    match parent {
+        [parent] => (parent.clone(), ruby.join(".")),
+        _ => (parent.concat(), ruby.concat()),
+    }
+}
+
+/// Split text by a designated character which is not escaped
+fn split_unescaped(text: &str, separator: char) -> Vec<&str> {
+    let mut segments = Vec::new();
+    let mut rest = text;
+    while let Some(position) = find_unescaped(rest, &[separator]) {
+        segments.push(&rest[..position]);
+        rest = &rest[position + separator.len_utf8()..];
+    }
+    segments.push(rest);
+    segments
+}
+
+/// Characters which can be escaped by `\`
+///
+/// `/` and `@` are escapable so that `\//` or `\@body` at line head is a literal text instead of a comment line or a marker.
+const ESCAPABLE: [char; 7] = ['\\', '[', ']', ':', '.', '/', '@'];
+
+/// Take a character at head of the text, and its length in the text
+fn unescaped_char(text: &str) -> (char, usize) {
+    let mut characters = text.chars();
+    let character = characters.next().unwrap_or_default();
+    match characters.next() {
+        Some(escaped) if character == '\\' && ESCAPABLE.contains(&escaped) => (escaped, 1 + escaped.len_utf8()),
+        _ => (character, character.len_utf8()),
+    }
+}
+
+/// Remove escape characters from the text
+fn unescape(text: &str) -> String {
+    let mut unescaped = String::new();
+    let mut position = 0;
+    while position < text.len() {
+        let (character, length) = unescaped_char(&text[position..]);
+        unescaped.push(character);
+        position += length;
+    }
+    unescaped
+}
+
+/// Find byte position of a designated character which is not escaped
+fn find_unescaped(text: &str, characters: &[char]) -> Option<usize> {
+    let mut position = 0;
+    while position < text.len() {
+        let (character, length) = unescaped_char(&text[position..]);
+        if length == character.len_utf8() && characters.contains(&character) {
+            return Some(position);
+        }
+        position += length;
+    }
+    None
+}
+
+/// Parse exclamation which starts at the position
+fn exclamation(text: &str, position: usize) -> Option<Node> {
+    let rest = &text[position..];
+    let length = ["!?", "?!", "！", "？", "‼"]
+        .iter()
+        .find(|exclamation| rest.starts_with(*exclamation))?
+        .len();
+    Some(Node::Exclamation {
+        text: rest[..length].to_string(),
+        span: position..position + length,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(text: &str, span: Range<usize>) -> Node {
+        Node::Text {
+            text: text.to_string(),
+            span,
+        }
+    }
+
+    #[test]
+    fn parse_should_parse_empty_text() {
+        assert_eq!(parse(""), vec![]);
+    }
+
+    #[test]
+    fn parse_should_parse_ruby() {
+        assert_eq!(
+            parse("[名前:なまえ]は"),
+            vec![
+                Node::Ruby {
+                    parent: "名前".to_string(),
+                    ruby: "なまえ".to_string(),
+                    span: 0..18
+                },
+                text("は", 18..21)
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_should_parse_kenten_style() {
+        assert_eq!(
+            parse("[猫:.*][犬:.]"),
+            vec![
+                Node::Kenten {
+                    text: "猫".to_string(),
+                    style: Some(KentenStyle::Sesame),
+                    span: 0..8
+                },
+                Node::Kenten {
+                    text: "犬".to_string(),
+                    style: None,
+                    span: 8..15
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_should_keep_ruby_of_bare_kenten_marks() {
+        assert_eq!(
+            parse("[丸:o][星:*]"),
+            vec![
+                Node::Ruby {
+                    parent: "丸".to_string(),
+                    ruby: "o".to_string(),
+                    span: 0..7
+                },
+                Node::Ruby {
+                    parent: "星".to_string(),
+                    ruby: "*".to_string(),
+                    span: 7..14
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_should_not_parse_ruby_across_brackets() {
+        assert_eq!(
+            parse("[名前は[太郎:たろう]"),
+            vec![
+                text("[名前は", 0..10),
+                Node::Ruby {
+                    parent: "太郎".to_string(),
+                    ruby: "たろう".to_string(),
+                    span: 10..28
+                }
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_should_parse_unclosed_bracket_as_text() {
+        let source = "私の[名前:なまえは[太郎:たろうです";
+        assert_eq!(parse(source), vec![text(source, 0..source.len())]);
+    }
+
+    #[test]
+    fn parse_should_parse_bracket_without_colon_as_text() {
+        assert_eq!(parse("[猫]"), vec![text("[猫]", 0..5)]);
+    }
+
+    #[test]
+    fn parse_should_parse_every_comment() {
+        assert_eq!(
+            parse("[#a]猫[#b]"),
+            vec![
+                Node::Comment {
+                    text: "a".to_string(),
+                    span: 0..4
+                },
+                text("猫", 4..7),
+                Node::Comment {
+                    text: "b".to_string(),
+                    span: 7..11
+                }
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_should_parse_comment_with_colon() {
+        assert_eq!(
+            parse("[#a:b]"),
+            vec![Node::Comment {
+                text: "a:b".to_string(),
+                span: 0..6
+            }]
+        );
+    }
+
+    #[test]
+    fn render_should_drop_every_comment() {
+        assert_eq!(render(&parse("猫[#a]と[#b]犬"), Format::Narou), "猫と犬");
+    }
+
+    #[test]
+    fn render_should_not_insert_space_before_closing_bracket() {
+        assert_eq!(render(&parse("「猫！[#a]」犬？"), Format::Narou), "「猫！」犬？");
+    }
+
+    #[test]
+    fn render_should_escape_ruby() {
+        assert_eq!(
+            render(&parse("[<猫>:&]"), Format::Html),
+            "<ruby>&lt;猫&gt;<rt>&amp;</rt></ruby>"
+        );
+    }
+
+    #[test]
+    fn parse_should_parse_split_ruby_per_character() {
+        assert_eq!(
+            parse("[東.京.都:とう.きょう.と]"),
+            vec![Node::SplitRuby {
+                parent: vec!["東".to_string(), "京".to_string(), "都".to_string()],
+                ruby: vec!["とう".to_string(), "きょう".to_string(), "と".to_string()],
+                span: 0..34
+            }]
+        );
+    }
+
+    #[test]
+    fn render_should_split_ruby_per_character_of_parent() {
+        let nodes = parse("[東京都:とう.きょう.と]");
+        assert_eq!(render(&nodes, Format::Narou), "｜東《とう》｜京《きょう》｜都《と》");
+        assert!(validate(&nodes).is_empty());
+    }
+
+    #[test]
+    fn parse_should_keep_ruby_whose_segments_do_not_match_characters_as_single_ruby() {
+        let nodes = parse("[サイト:www.example.co.jp]");
+        assert_eq!(render(&nodes, Format::Narou), "｜サイト《www.example.co.jp》");
+        assert!(validate(&nodes).is_empty());
+        assert_eq!(
+            render(&parse(r"[URL:www\.example\.com]"), Format::Narou),
+            "｜URL《www.example.com》"
+        );
+    }
+
+    #[test]
+    fn parse_should_parse_split_ruby_per_segment() {
+        assert_eq!(
+            parse("[東京.都:とうきょう.と]"),
+            vec![Node::SplitRuby {
+                parent: vec!["東京".to_string(), "都".to_string()],
+                ruby: vec!["とうきょう".to_string(), "と".to_string()],
+                span: 0..32
+            }]
+        );
+    }
+
+    #[test]
+    fn parse_should_parse_ruby_with_empty_segment_as_group_ruby() {
+        assert_eq!(
+            parse("[博士:Dr.]"),
+            vec![Node::Ruby {
+                parent: "博士".to_string(),
+                ruby: "Dr.".to_string(),
+                span: 0..12
+            }]
+        );
+    }
+
+    #[test]
+    fn parse_should_parse_ruby_with_escaped_dot_as_group_ruby() {
+        assert_eq!(
+            parse(r"[東京:とう\.きょう]"),
+            vec![Node::Ruby {
+                parent: "東京".to_string(),
+                ruby: "とう.きょう".to_string(),
+                span: 0..26
+            }]
+        );
+    }
+
+    #[test]
+    fn render_should_render_split_ruby_per_segment() {
+        assert_eq!(
+            render(&parse("[東.京.都:とう.きょう.と]"), Format::Narou),
+            "｜東《とう》｜京《きょう》｜都《と》"
+        );
+        assert_eq!(
+            render(&parse("[東京.都:とうきょう.と]"), Format::Html),
+            "<ruby>東京<rt>とうきょう</rt></ruby><ruby>都<rt>と</rt></ruby>"
+        );
+    }
+
+    #[test]
+    fn render_should_render_mismatched_split_ruby_as_group_ruby() {
+        assert_eq!(
+            render(&parse("[東.京.都:とう.きょう]"), Format::Narou),
+            "｜東京都《とうきょう》"
+        );
+    }
+
+    #[test]
+    fn validate_should_accept_matched_split_ruby() {
+        assert_eq!(validate(&parse("[東.京.都:とう.きょう.と][猫:ねこ]")), vec![]);
+    }
+
+    #[test]
+    fn validate_for_should_check_every_segment_of_split_ruby() {
+        let nodes = parse("[東.京.都:とう.きょうきょうきょうきょう.と]");
+        assert_eq!(
+            validate_for(&nodes, Format::Narou)
+                .into_iter()
+                .map(|(_, error)| error)
+                .collect::<Vec<_>>(),
+            vec![MarkupError::RubyTooLong { length: 12, limit: 10 }]
+        );
+    }
+
+    #[test]
+    fn validate_for_should_check_kenten_for_narou() {
+        let nodes = parse("[あいうえおかきくけこさ:.]");
+        assert_eq!(
+            validate_for(&nodes, Format::Narou)
+                .into_iter()
+                .map(|(_, error)| error)
+                .collect::<Vec<_>>(),
+            vec![MarkupError::RubyParentTooLong { length: 11, limit: 10 }]
+        );
+    }
+
+    #[test]
+    fn validate_for_should_report_disallowed_character() {
+        let nodes = parse("[猫:ね（こ）]");
+        assert_eq!(
+            validate_for(&nodes, Format::Narou)
+                .into_iter()
+                .map(|(_, error)| error)
+                .collect::<Vec<_>>(),
+            vec![MarkupError::DisallowedRubyCharacter('（')]
+        );
+    }
+
+    #[test]
+    fn parse_should_parse_escaped_brackets_as_text() {
+        assert_eq!(parse(r"\[10:30\]と\\"), vec![text("[10:30]と\\", 0..14)]);
+    }
+
+    #[test]
+    fn parse_should_parse_escaped_characters_in_markup() {
+        assert_eq!(
+            parse(r"[10\:30\]:じゅうじ]"),
+            vec![Node::Ruby {
+                parent: "10:30]".to_string(),
+                ruby: "じゅうじ".to_string(),
+                span: 0..23
+            }]
+        );
+    }
+
+    #[test]
+    fn parse_should_parse_backslash_before_other_character_as_text() {
+        assert_eq!(parse(r"\猫"), vec![text(r"\猫", 0..4)]);
+    }
+
+    #[test]
+    fn escape_should_escape_backslash() {
+        assert_eq!(escape(r"a\b"), r"a\\b");
+    }
+
+    #[test]
+    fn parse_should_parse_half_width_exclamations() {
+        assert_eq!(
+            parse("猫!?犬!"),
+            vec![
+                text("猫", 0..3),
+                Node::Exclamation {
+                    text: "!?".to_string(),
+                    span: 3..5
+                },
+                text("犬!", 5..9)
+            ]
+        );
+    }
+}
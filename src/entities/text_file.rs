@@ -1,7 +1,17 @@
-use crate::entities::chapter::Chapter;
-use crate::errors::TextFileOutputError;
+use crate::entities::chapter::{Chapter, Section};
+use crate::entities::glossary::{Glossary, GlossaryMode};
+use crate::entities::line::{IndentRules, SourceRules};
+use crate::entities::punctuation::PunctuationRules;
+use crate::entities::scene_break::SceneBreakStyle;
+use crate::entities::syntax::KentenStyle;
+use crate::entities::width::WidthRules;
+use crate::errors::{Diagnostic, TextFileOutputError};
+use crate::format::epub::{self, ContentDocument};
+use crate::format::{docx, Format, Options};
+use crate::import::{self, Dialect};
 use std::fs;
 use std::fs::File;
+use std::io;
 use std::io::Write;
 use std::path::Path;
 
@@ -27,7 +37,74 @@ impl<'file_handling> TextFile {
     /// TextFile::new("./path/to/source/file.txt").unwrap();
     /// ```
     pub fn new(path_from: &str) -> Result<Self, Box<dyn std::error::Error + 'static>> {
+        Self::new_in(path_from, Dialect::Naromat)
+    }
+
+    /// Constructor from a file written in designated dialect
+    ///
+    /// # Fail
+    ///
+    /// * Path format is invalid
+    /// * A file designated by a path is not exists
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use naromat::entities::text_file::TextFile;
+    /// use naromat::import::Dialect;
+    ///
+    /// TextFile::new_in("./path/to/source/aozora.txt", Dialect::Aozora).unwrap();
+    /// ```
+    pub fn new_in(path_from: &str, dialect: Dialect) -> Result<Self, Box<dyn std::error::Error + 'static>> {
+        Self::new_with(path_from, dialect, &SourceRules::default())
+    }
+
+    /// Constructor from a file written in designated dialect, whose comments and sentences are read with designated rules
+    ///
+    /// # Fail
+    ///
+    /// * Path format is invalid
+    /// * A file designated by a path is not exists
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use naromat::entities::line::SourceRules;
+    /// use naromat::entities::text_file::TextFile;
+    /// use naromat::import::Dialect;
+    ///
+    /// let rules = SourceRules { comment: "#".to_string() };
+    /// TextFile::new_with("./path/to/source/file.txt", Dialect::Naromat, &rules).unwrap();
+    /// ```
+    pub fn new_with(
+        path_from: &str,
+        dialect: Dialect,
+        rules: &SourceRules,
+    ) -> Result<Self, Box<dyn std::error::Error + 'static>> {
         let text = fs::read_to_string(path_from)?;
+        Ok(Self {
+            chapter: Chapter::new_with(text.as_str(), dialect, rules),
+        })
+    }
+
+    /// Constructor from Word document(.docx)
+    ///
+    /// The document is imported as naromat source text, see `naromat::import::docx::import`.
+    ///
+    /// # Fail
+    ///
+    /// * A file designated by a path is not exists
+    /// * A file is not a valid Word document
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use naromat::entities::text_file::TextFile;
+    ///
+    /// TextFile::from_docx("./path/to/source/file.docx").unwrap();
+    /// ```
+    pub fn from_docx(path_from: &str) -> Result<Self, Box<dyn std::error::Error + 'static>> {
+        let text = import::docx::import(File::open(path_from)?)?;
         Ok(Self {
             chapter: Chapter::new(text.as_str()),
         })
@@ -47,11 +124,270 @@ impl<'file_handling> TextFile {
     /// let text = TextFile::new("./path/to/source/file.txt").unwrap();
     /// text.format_and_save("./path/to/save.txt");
     /// ```
-    pub fn format_and_save(self, path_to: &'file_handling str) -> Result<(), TextFileOutputError> {
-        Self::touch_file(path_to).and_then(|file| self.save_file(file))
+    pub fn format_and_save(self, path_to: &'file_handling str) -> Result<(), TextFileOutputError<'file_handling>> {
+        self.format_and_save_as(path_to, Format::Narou)
+    }
+
+    /// Format file text to designated format and save
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use naromat::entities::text_file::TextFile;
+    /// use naromat::format::Format;
+    ///
+    /// let text = TextFile::new("./path/to/source/file.txt").unwrap();
+    /// text.format_and_save_as("./path/to/save.txt", Format::Kakuyomu);
+    /// ```
+    pub fn format_and_save_as(
+        self,
+        path_to: &'file_handling str,
+        format: Format,
+    ) -> Result<(), TextFileOutputError<'file_handling>> {
+        self.format_and_save_with(path_to, format, &Options::default())
     }
 
-    fn touch_file(path_to: &'file_handling str) -> Result<File, TextFileOutputError> {
+    /// Format file text to designated format with options and save
+    ///
+    /// A file which has preface(`@preface`) or afterword(`@afterword`) is saved to up to three files
+    /// (`episode.preface.txt`, `episode.txt` and `episode.afterword.txt`) unless `combine_sections` option is set.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use naromat::entities::text_file::TextFile;
+    /// use naromat::format::{Format, Options};
+    ///
+    /// let text = TextFile::new("./path/to/source/file.txt").unwrap();
+    /// let options = Options {
+    ///     vertical: true,
+    ///     ..Options::default()
+    /// };
+    /// text.format_and_save_with("./path/to/save.html", Format::Html, &options);
+    /// ```
+    pub fn format_and_save_with(
+        self,
+        path_to: &'file_handling str,
+        format: Format,
+        options: &Options,
+    ) -> Result<(), TextFileOutputError<'file_handling>> {
+        if options.combine_sections || !self.chapter.has_sections() {
+            return Self::touch_file(path_to).and_then(|file| self.save_file(file, format, options));
+        }
+        let sections = self.chapter.into_sections();
+        let paths: Vec<String> = sections
+            .iter()
+            .map(|(section, _)| Self::section_path(path_to, *section))
+            .collect();
+        if let Some(path) = paths.iter().find(|path| Path::new(path).exists()) {
+            if path == path_to {
+                return Err(TextFileOutputError::AlreadyExists(path_to));
+            }
+            let message = format!("File {} is already exists", path);
+            return Err(TextFileOutputError::CannotCreate(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                message,
+            )));
+        }
+        for ((_, chapter), path) in sections.into_iter().zip(&paths) {
+            let file = File::create(path).map_err(TextFileOutputError::CannotCreate)?;
+            Self { chapter }.save_file(file, format, options)?;
+        }
+        Ok(())
+    }
+
+    /// Path to save a section of an episode
+    ///
+    /// Body is saved to the designated path, and the other sections are saved
+    /// to the path which has the section name before its extension(`episode.preface.txt`).
+    fn section_path(path_to: &str, section: Section) -> String {
+        if section == Section::Body {
+            return path_to.to_string();
+        }
+        let path = Path::new(path_to);
+        let path = match path.extension() {
+            Some(extension) => path.with_extension(format!("{}.{}", section.name(), extension.to_string_lossy())),
+            None => path.with_extension(section.name()),
+        };
+        path.to_string_lossy().to_string()
+    }
+
+    /// Add ruby of glossary terms
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use naromat::entities::glossary::{Glossary, GlossaryMode};
+    /// use naromat::entities::text_file::TextFile;
+    ///
+    /// let glossary = Glossary::load("./path/to/glossary.tsv").unwrap();
+    /// let text = TextFile::new("./path/to/source/file.txt").unwrap();
+    /// text.with_glossary(&glossary, GlossaryMode::Every).format_and_save("./path/to/save.txt");
+    /// ```
+    pub fn with_glossary(self, glossary: &Glossary, mode: GlossaryMode) -> Self {
+        Self {
+            chapter: self.chapter.with_glossary(glossary, mode),
+        }
+    }
+
+    /// Normalize punctuation with designated rules
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use naromat::entities::punctuation::PunctuationRules;
+    /// use naromat::entities::text_file::TextFile;
+    ///
+    /// let text = TextFile::new("./path/to/source/file.txt").unwrap();
+    /// text.with_punctuation(&PunctuationRules::default()).format_and_save("./path/to/save.txt");
+    /// ```
+    pub fn with_punctuation(self, rules: &PunctuationRules) -> Self {
+        Self {
+            chapter: self.chapter.with_punctuation(rules),
+        }
+    }
+
+    /// Normalize width of digits, katakana and latin letters with designated rules
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use naromat::entities::text_file::TextFile;
+    /// use naromat::entities::width::WidthRules;
+    /// use naromat::format::Format;
+    ///
+    /// let text = TextFile::new("./path/to/source/file.txt").unwrap();
+    /// text.with_width(&WidthRules::for_format(Format::Epub)).format_and_save("./path/to/save.txt");
+    /// ```
+    pub fn with_width(self, rules: &WidthRules) -> Self {
+        Self {
+            chapter: self.chapter.with_width(rules),
+        }
+    }
+
+    /// Use designated whitespace after exclamation instead of a 2 byte whitespace
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use naromat::entities::text_file::TextFile;
+    ///
+    /// let text = TextFile::new("./path/to/source/file.txt").unwrap();
+    /// text.with_exclamation_space(" ").format_and_save("./path/to/save.txt");
+    /// ```
+    pub fn with_exclamation_space(self, space: &str) -> Self {
+        Self {
+            chapter: self.chapter.with_exclamation_space(space),
+        }
+    }
+
+    /// Indent paragraphs with designated rules
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use naromat::entities::line::IndentRules;
+    /// use naromat::entities::text_file::TextFile;
+    ///
+    /// let rules = IndentRules {
+    ///     preserve: false,
+    ///     ..IndentRules::default()
+    /// };
+    /// let text = TextFile::new("./path/to/source/file.txt").unwrap();
+    /// text.with_indent_rules(&rules).format_and_save("./path/to/save.txt");
+    /// ```
+    pub fn with_indent_rules(self, rules: &IndentRules) -> Self {
+        Self {
+            chapter: self.chapter.with_indent_rules(rules),
+        }
+    }
+
+    /// Use designated style for kenten which has no explicit style
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use naromat::entities::syntax::KentenStyle;
+    /// use naromat::entities::text_file::TextFile;
+    ///
+    /// let text = TextFile::new("./path/to/source/file.txt").unwrap();
+    /// text.with_kenten_style(KentenStyle::Sesame).format_and_save("./path/to/save.txt");
+    /// ```
+    pub fn with_kenten_style(self, style: KentenStyle) -> Self {
+        Self {
+            chapter: self.chapter.with_kenten_style(style),
+        }
+    }
+
+    /// Recognize and render scene breaks with designated style
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use naromat::entities::scene_break::SceneBreakStyle;
+    /// use naromat::entities::text_file::TextFile;
+    ///
+    /// let style = SceneBreakStyle {
+    ///     blank_run: Some(3),
+    ///     ..SceneBreakStyle::default()
+    /// };
+    /// let text = TextFile::new("./path/to/source/file.txt").unwrap();
+    /// text.with_scene_breaks(style).format_and_save("./path/to/save.txt");
+    /// ```
+    pub fn with_scene_breaks(self, style: SceneBreakStyle) -> Self {
+        Self {
+            chapter: self.chapter.with_scene_breaks(style),
+        }
+    }
+
+    /// Markup errors found in this file
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use naromat::entities::text_file::TextFile;
+    ///
+    /// let text = TextFile::new("./path/to/source/file.txt").unwrap();
+    /// for diagnostic in text.diagnostics() {
+    ///     println!("file.txt:{}", diagnostic);
+    /// }
+    /// ```
+    pub fn diagnostics(&self) -> Vec<Diagnostic> {
+        self.chapter.diagnostics()
+    }
+
+    /// Markup errors found in this file, including violations of limits of designated format
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use naromat::entities::text_file::TextFile;
+    /// use naromat::format::Format;
+    ///
+    /// let text = TextFile::new("./path/to/source/file.txt").unwrap();
+    /// for diagnostic in text.diagnostics_for(Format::Narou) {
+    ///     println!("file.txt:{}", diagnostic);
+    /// }
+    /// ```
+    pub fn diagnostics_for(&self, format: Format) -> Vec<Diagnostic> {
+        self.chapter.diagnostics_for(format)
+    }
+
+    /// Take formatted chapter of this file
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use naromat::entities::text_file::TextFile;
+    ///
+    /// let text = TextFile::new("./path/to/source/file.txt").unwrap();
+    /// println!("{}", text.into_chapter().get());
+    /// ```
+    pub fn into_chapter(self) -> Chapter {
+        self.chapter
+    }
+
+    pub(crate) fn touch_file(path_to: &'file_handling str) -> Result<File, TextFileOutputError<'file_handling>> {
         if Path::new(path_to).exists() {
             return Err(TextFileOutputError::AlreadyExists(path_to));
         }
@@ -61,8 +397,16 @@ impl<'file_handling> TextFile {
         }
     }
 
-    fn save_file(self, mut file: File) -> Result<(), TextFileOutputError<'file_handling>> {
-        let result = match writeln!(file, "{}", self.chapter.get()) {
+    fn save_file(
+        self,
+        mut file: File,
+        format: Format,
+        options: &Options,
+    ) -> Result<(), TextFileOutputError<'file_handling>> {
+        if let Format::Epub | Format::Docx = format {
+            return self.save_packed_file(file, format, options);
+        }
+        let result = match writeln!(file, "{}", self.chapter.get_with(format, options)) {
             Ok(_) => Ok(()),
             Err(cause) => return Err(TextFileOutputError::CannotWrite(cause)),
         };
@@ -71,13 +415,41 @@ impl<'file_handling> TextFile {
             Err(cause) => Err(TextFileOutputError::CannotFlush(cause)),
         }
     }
+
+    /// Save a format which is a zip package of documents
+    fn save_packed_file(
+        self,
+        file: File,
+        format: Format,
+        options: &Options,
+    ) -> Result<(), TextFileOutputError<'file_handling>> {
+        let document = self.chapter.get_with(format, options);
+        let result = match format {
+            Format::Epub => {
+                let document = ContentDocument {
+                    title: options.title.clone().unwrap_or_default(),
+                    xhtml: document,
+                };
+                epub::write(file, &[document], options)
+            }
+            _ => docx::write(file, &document, options),
+        };
+        match result {
+            Ok(_) => Ok(()),
+            Err(cause) => Err(TextFileOutputError::CannotWrite(io::Error::from(cause))),
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::TextFile;
+    use crate::format::{Format, Options};
+    use crate::import::Dialect;
     use file_diff::diff;
+    use std::io::Read;
     use std::{fs, time};
+    use zip::ZipArchive;
 
     #[test]
     fn can_save_formatted_text() {
@@ -102,6 +474,89 @@ mod tests {
         assert!(is_target_text_is_same_to_reference);
     }
 
+    #[test]
+    fn can_save_html() {
+        // given
+        let source_file_path = "./resources/test/entities/file/source.txt";
+        let target_file_path = "./resources/test/entities/file/target-html.html";
+        let source_file = TextFile::new(source_file_path).unwrap();
+
+        // when
+        source_file.format_and_save_as(target_file_path, Format::Html).unwrap();
+
+        // then
+        let html = fs::read_to_string(target_file_path).unwrap();
+        // teardown
+        fs::remove_file(target_file_path).unwrap();
+        // assert
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains("<ruby>貴族<rt>わたしたち</rt></ruby>"));
+    }
+
+    #[test]
+    fn can_save_docx() {
+        // given
+        let source_file_path = "./resources/test/entities/file/source.txt";
+        let target_file_path = "./resources/test/entities/file/target-docx.docx";
+        let source_file = TextFile::new(source_file_path).unwrap();
+
+        // when
+        source_file.format_and_save_as(target_file_path, Format::Docx).unwrap();
+
+        // then
+        let mut archive = ZipArchive::new(fs::File::open(target_file_path).unwrap()).unwrap();
+        let mut document = String::new();
+        archive
+            .by_name("word/document.xml")
+            .unwrap()
+            .read_to_string(&mut document)
+            .unwrap();
+        // teardown
+        fs::remove_file(target_file_path).unwrap();
+        // assert
+        assert!(document.contains("<w:t>わたしたち</w:t>"));
+        assert!(document.contains("<w:ind w:firstLineChars=\"100\"/>"));
+    }
+
+    #[test]
+    fn can_load_docx() {
+        // given
+        let source_file_path = "./resources/test/entities/file/source.txt";
+        let docx_file_path = "./resources/test/entities/file/target-import.docx";
+        TextFile::new(source_file_path)
+            .unwrap()
+            .format_and_save_as(docx_file_path, Format::Docx)
+            .unwrap();
+
+        // when
+        let imported = TextFile::from_docx(docx_file_path);
+
+        // teardown
+        fs::remove_file(docx_file_path).unwrap();
+        // assert
+        assert!(imported.unwrap().into_chapter().get().contains("｜貴族《わたしたち》"));
+    }
+
+    #[test]
+    fn can_load_aozora_text() {
+        // given
+        let source_file_path = "./resources/test/entities/file/aozora.txt";
+
+        // when
+        let loaded = TextFile::new_in(source_file_path, Dialect::Aozora).unwrap();
+        let reloaded = TextFile::new_in(source_file_path, Dialect::Aozora).unwrap();
+
+        // assert
+        assert_eq!(
+            loaded.into_chapter().get(),
+            "　｜吾輩《わがはい》は猫である。｜名前《・・》はまだ無い。\n◇\n　　どこで生れたかとんと見当がつかぬ。"
+        );
+        assert_eq!(
+            reloaded.into_chapter().get_as(Format::Aozora),
+            "　｜吾輩《わがはい》は猫である。名前［＃「名前」に傍点］はまだ無い。\n［＃改ページ］\n　　どこで生れたかとんと見当がつかぬ。"
+        );
+    }
+
     #[test]
     fn return_error_when_target_file_already_exists() {
         // given
@@ -141,4 +596,55 @@ mod tests {
         // assert
         assert!(is_return_error);
     }
+
+    #[test]
+    fn can_save_sections_to_separate_files() {
+        // given
+        let source_file_path = "./resources/test/entities/file/sections.txt";
+        let target_file_path = "./resources/test/entities/file/target-sections.txt";
+        let preface_file_path = "./resources/test/entities/file/target-sections.preface.txt";
+        let afterword_file_path = "./resources/test/entities/file/target-sections.afterword.txt";
+        let source_file = TextFile::new(source_file_path).unwrap();
+
+        // when
+        source_file.format_and_save(target_file_path).unwrap();
+
+        // then
+        let body = fs::read_to_string(target_file_path).unwrap();
+        let preface = fs::read_to_string(preface_file_path).unwrap();
+        let afterword = fs::read_to_string(afterword_file_path).unwrap();
+        // teardown
+        fs::remove_file(target_file_path).unwrap();
+        fs::remove_file(preface_file_path).unwrap();
+        fs::remove_file(afterword_file_path).unwrap();
+        // assert
+        assert_eq!(preface, "　いつもお読みいただきありがとうございます。\n");
+        assert_eq!(body, "　我が輩は｜猫《ねこ》である。\n\n　名前はまだない。\n");
+        assert_eq!(afterword, "　次回は犬の話です。\n");
+    }
+
+    #[test]
+    fn can_save_sections_to_a_combined_file() {
+        // given
+        let source_file_path = "./resources/test/entities/file/sections.txt";
+        let target_file_path = "./resources/test/entities/file/target-sections-combined.txt";
+        let source_file = TextFile::new(source_file_path).unwrap();
+        let options = Options {
+            combine_sections: true,
+            ..Options::default()
+        };
+
+        // when
+        source_file
+            .format_and_save_with(target_file_path, Format::Narou, &options)
+            .unwrap();
+
+        // then
+        let combined = fs::read_to_string(target_file_path).unwrap();
+        // teardown
+        fs::remove_file(target_file_path).unwrap();
+        // assert
+        assert!(combined.starts_with("【まえがき】\n　いつもお読みいただきありがとうございます。\n\n【本文】\n"));
+        assert!(combined.ends_with("\n\n【あとがき】\n　次回は犬の話です。\n"));
+    }
 }
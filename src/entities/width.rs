@@ -0,0 +1,262 @@
+use crate::entities::syntax::Node;
+use crate::format::Format;
+
+/// Rules to normalize width of characters for vertical readers
+///
+/// * `digits` - runs of one digit or three or more digits become full width, and runs of two digits half width
+/// * `tate_chu_yoko` - runs of two digits are set horizontally in vertical text(html/epub only)
+/// * `katakana` - half width katakana become full width
+/// * `latin` - latin letters become full width
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WidthRules {
+    pub digits: bool,
+    pub tate_chu_yoko: bool,
+    pub katakana: bool,
+    pub latin: bool,
+}
+
+impl Default for WidthRules {
+    fn default() -> Self {
+        Self {
+            digits: true,
+            tate_chu_yoko: false,
+            katakana: true,
+            latin: false,
+        }
+    }
+}
+
+/// Half width katakana and punctuation, which are followed by full width ones in `FULL_WIDTH_KATAKANA`
+const HALF_WIDTH_KATAKANA: &str = "｡｢｣､･ｦｧｨｩｪｫｬｭｮｯｰｱｲｳｴｵｶｷｸｹｺｻｼｽｾｿﾀﾁﾂﾃﾄﾅﾆﾇﾈﾉﾊﾋﾌﾍﾎﾏﾐﾑﾒﾓﾔﾕﾖﾗﾘﾙﾚﾛﾜﾝﾞﾟ";
+const FULL_WIDTH_KATAKANA: &str = "。「」、・ヲァィゥェォャュョッーアイウエオカキクケコサシスセソタチツテトナニヌネノハヒフヘホマミムメモヤユヨラリルレロワン゛゜";
+
+/// Distance between code points of ASCII and full width forms
+const FULL_WIDTH_OFFSET: u32 = 0xFEE0;
+
+impl WidthRules {
+    /// Rules for designated format
+    ///
+    /// Runs of two digits are marked for tate-chu-yoko only in html and epub.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use naromat::entities::width::WidthRules;
+    /// use naromat::format::Format;
+    ///
+    /// assert!(WidthRules::for_format(Format::Epub).tate_chu_yoko);
+    /// assert!(!WidthRules::for_format(Format::Narou).tate_chu_yoko);
+    /// ```
+    pub fn for_format(format: Format) -> Self {
+        Self {
+            tate_chu_yoko: matches!(format, Format::Html | Format::Epub),
+            ..Self::default()
+        }
+    }
+
+    /// Normalize width of plain text
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use naromat::entities::width::WidthRules;
+    ///
+    /// let rules = WidthRules::default();
+    /// assert_eq!(rules.normalize("第3話 2０人のｶﾞｲﾄﾞと100匹のAI"), "第３話 20人のガイドと１００匹のAI");
+    /// let rules = WidthRules {
+    ///     latin: true,
+    ///     ..WidthRules::default()
+    /// };
+    /// assert_eq!(rules.normalize("AI"), "ＡＩ");
+    /// ```
+    pub fn normalize(&self, text: &str) -> String {
+        self.segments(text).into_iter().map(|(text, _)| text).collect()
+    }
+
+    /// Normalize width in text nodes, parents of ruby and text of kenten
+    ///
+    /// Runs of two digits in text nodes are split to tate-chu-yoko nodes which have the span of the text node.
+    /// Ruby and kenten keep runs of two digits half width without tate-chu-yoko.
+    pub(crate) fn apply(&self, nodes: Vec<Node>) -> Vec<Node> {
+        let mut normalized = Vec::new();
+        for node in nodes {
+            match node {
+                Node::Text { text, span } => {
+                    for (text, is_tate_chu_yoko) in self.segments(&text) {
+                        normalized.push(if is_tate_chu_yoko {
+                            Node::TateChuYoko {
+                                text,
+                                span: span.clone(),
+                            }
+                        } else {
+                            Node::Text {
+                                text,
+                                span: span.clone(),
+                            }
+                        });
+                    }
+                }
+                Node::Ruby { parent, ruby, span } => normalized.push(Node::Ruby {
+                    parent: self.normalize(&parent),
+                    ruby,
+                    span,
+                }),
+                Node::SplitRuby { parent, ruby, span } => normalized.push(Node::SplitRuby {
+                    parent: parent.iter().map(|segment| self.normalize(segment)).collect(),
+                    ruby,
+                    span,
+                }),
+                Node::Kenten { text, style, span } => normalized.push(Node::Kenten {
+                    text: self.normalize(&text),
+                    style,
+                    span,
+                }),
+                node => normalized.push(node),
+            }
+        }
+        normalized
+    }
+
+    /// Split text to normalized segments, which are paired with true if it is set in tate-chu-yoko
+    fn segments(&self, text: &str) -> Vec<(String, bool)> {
+        let mut segments = Vec::new();
+        let mut plain = String::new();
+        let mut characters = text.chars().peekable();
+        while let Some(character) = characters.next() {
+            if is_digit(character) {
+                let mut digits = vec![character];
+                while let Some(digit) = characters.next_if(|character| is_digit(*character)) {
+                    digits.push(digit);
+                }
+                if self.tate_chu_yoko && digits.len() == 2 {
+                    if !plain.is_empty() {
+                        segments.push((std::mem::take(&mut plain), false));
+                    }
+                    segments.push((digits.into_iter().map(half_width).collect(), true));
+                } else if self.digits && digits.len() == 2 {
+                    plain.extend(digits.into_iter().map(half_width));
+                } else if self.digits {
+                    plain.extend(digits.into_iter().map(full_width));
+                } else {
+                    plain.extend(digits);
+                }
+            } else if self.katakana && HALF_WIDTH_KATAKANA.contains(character) {
+                let mark = characters.next_if(|mark| matches!(mark, 'ﾞ' | 'ﾟ'));
+                plain.push_str(&full_width_katakana(character, mark));
+            } else if self.latin && character.is_ascii_alphabetic() {
+                plain.push(full_width(character));
+            } else {
+                plain.push(character);
+            }
+        }
+        if !plain.is_empty() {
+            segments.push((plain, false));
+        }
+        segments
+    }
+}
+
+/// Return true if a character is a half width or full width digit
+fn is_digit(character: char) -> bool {
+    matches!(character, '0'..='9' | '０'..='９')
+}
+
+/// Full width form of an ASCII character
+fn full_width(character: char) -> char {
+    match character {
+        '!'..='~' => char::from_u32(character as u32 + FULL_WIDTH_OFFSET).unwrap_or(character),
+        _ => character,
+    }
+}
+
+/// Half width form of a full width digit
+fn half_width(character: char) -> char {
+    match character {
+        '０'..='９' => char::from_u32(character as u32 - FULL_WIDTH_OFFSET).unwrap_or(character),
+        _ => character,
+    }
+}
+
+/// Full width katakana of a half width one which is followed by a voiced sound mark(`ﾞ`, `ﾟ`)
+fn full_width_katakana(character: char, mark: Option<char>) -> String {
+    let index = HALF_WIDTH_KATAKANA.chars().position(|half| half == character);
+    let full = match index.and_then(|index| FULL_WIDTH_KATAKANA.chars().nth(index)) {
+        Some(full) => full,
+        None => return character.to_string(),
+    };
+    let voiced = match (full, mark) {
+        ('ウ', Some('ﾞ')) => Some('ヴ'),
+        (full, Some('ﾞ')) if "カキクケコサシスセソタチツテトハヒフヘホ".contains(full) => {
+            char::from_u32(full as u32 + 1)
+        }
+        (full, Some('ﾟ')) if "ハヒフヘホ".contains(full) => char::from_u32(full as u32 + 2),
+        _ => None,
+    };
+    match (voiced, mark) {
+        (Some(voiced), _) => voiced.to_string(),
+        (None, Some(mark)) => format!("{}{}", full, full_width_katakana(mark, None)),
+        (None, None) => full.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entities::syntax;
+
+    #[test]
+    fn katakana_tables_should_have_same_length() {
+        assert_eq!(HALF_WIDTH_KATAKANA.chars().count(), FULL_WIDTH_KATAKANA.chars().count());
+    }
+
+    #[test]
+    fn normalize_should_convert_voiced_katakana() {
+        let rules = WidthRules::default();
+        assert_eq!(rules.normalize("ｳﾞｧｲｵﾘﾝとﾊﾟﾝ"), "ヴァイオリンとパン");
+        assert_eq!(rules.normalize("ｱﾞ"), "ア゛");
+    }
+
+    #[test]
+    fn normalize_should_keep_width_when_disabled() {
+        let rules = WidthRules {
+            digits: false,
+            katakana: false,
+            ..WidthRules::default()
+        };
+        assert_eq!(rules.normalize("3ｶ月と12日"), "3ｶ月と12日");
+    }
+
+    #[test]
+    fn apply_should_mark_two_digits_for_tate_chu_yoko() {
+        let rules = WidthRules::for_format(Format::Html);
+        let nodes = rules.apply(syntax::parse("１２月3日"));
+        assert_eq!(
+            nodes,
+            vec![
+                Node::TateChuYoko {
+                    text: "12".to_string(),
+                    span: 0..13
+                },
+                Node::Text {
+                    text: "月３日".to_string(),
+                    span: 0..13
+                },
+            ]
+        );
+        assert_eq!(
+            syntax::render(&nodes, Format::Html),
+            "<span class=\"tcy\">12</span>月３日"
+        );
+        assert_eq!(syntax::render(&nodes, Format::Narou), "12月３日");
+    }
+
+    #[test]
+    fn apply_should_normalize_ruby_parents_and_kenten() {
+        let rules = WidthRules::for_format(Format::Html);
+        let nodes = rules.apply(syntax::parse("[ｶﾞｲﾄﾞ:がいど]と[3.号:さん.ごう]の[ﾈｺ:.]"));
+        assert_eq!(
+            syntax::render(&nodes, Format::Narou),
+            "｜ガイド《がいど》と｜３《さん》｜号《ごう》の｜ネコ《・・》"
+        );
+    }
+}
@@ -1,6 +1,7 @@
 use std::fmt;
 use std::io;
 use std::path::Path;
+use zip::result::ZipError;
 
 #[derive(Debug)]
 pub enum TextFileOutputError<'file_handling> {
@@ -28,6 +29,7 @@ pub enum FileProcessError<'file_process> {
     InvalidPath(InvalidPathError<'file_process>),
     SaveError(TextFileOutputError<'file_process>),
     LoadError(Box<dyn std::error::Error>),
+    InvalidMarkup(Vec<Diagnostic>),
 }
 
 impl<'file_process> fmt::Display for FileProcessError<'file_process> {
@@ -62,3 +64,144 @@ impl<'file_handling> fmt::Display for InvalidPathError<'file_handling> {
         }
     }
 }
+
+#[derive(Debug)]
+pub enum FormatParseError {
+    Unknown(String),
+}
+
+impl fmt::Display for FormatParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            FormatParseError::Unknown(ref name) => write!(f, "Unknown format error: Format {} is not supported", name),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum ImportError {
+    CannotRead(io::Error),
+    InvalidPackage(ZipError),
+    InvalidXml(quick_xml::Error),
+}
+
+impl fmt::Display for ImportError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ImportError::CannotRead(ref error) => write!(f, "Cannot read error: {}", error),
+            ImportError::InvalidPackage(ref error) => write!(f, "Invalid package error: {}", error),
+            ImportError::InvalidXml(ref error) => write!(f, "Invalid XML error: {}", error),
+        }
+    }
+}
+
+impl std::error::Error for ImportError {}
+
+#[derive(Debug)]
+pub enum GlossaryError {
+    CannotRead(io::Error),
+    InvalidLine(usize),
+    InvalidToml(toml::de::Error),
+    InvalidReading(String),
+}
+
+impl fmt::Display for GlossaryError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            GlossaryError::CannotRead(ref error) => write!(f, "Cannot read error: {}", error),
+            GlossaryError::InvalidLine(line) => {
+                write!(
+                    f,
+                    "Invalid line error: Line {} must be a term and its reading separated by tab",
+                    line
+                )
+            }
+            GlossaryError::InvalidToml(ref error) => write!(f, "Invalid TOML error: {}", error),
+            GlossaryError::InvalidReading(ref term) => {
+                write!(
+                    f,
+                    "Invalid reading error: Reading of {} must be a non-empty string",
+                    term
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for GlossaryError {}
+
+#[derive(Debug)]
+pub enum ConfigError {
+    CannotRead(io::Error),
+    InvalidToml(toml::de::Error),
+    InvalidValue(String),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ConfigError::CannotRead(ref error) => write!(f, "Cannot read error: {}", error),
+            ConfigError::InvalidToml(ref error) => write!(f, "Invalid TOML error: {}", error),
+            ConfigError::InvalidValue(ref key) => write!(f, "Invalid value error: Value of {} is invalid", key),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MarkupError {
+    RubySegmentMismatch { parent: usize, ruby: usize },
+    RubyParentTooLong { length: usize, limit: usize },
+    RubyTooLong { length: usize, limit: usize },
+    DisallowedRubyCharacter(char),
+    UnterminatedBlockComment { closing: &'static str },
+}
+
+impl fmt::Display for MarkupError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            MarkupError::RubySegmentMismatch { parent, ruby } => write!(
+                f,
+                "Ruby segment mismatch error: {} parent segments for {} ruby segments",
+                parent, ruby
+            ),
+            MarkupError::RubyParentTooLong { length, limit } => write!(
+                f,
+                "Ruby parent too long error: {} characters exceed the limit of {}",
+                length, limit
+            ),
+            MarkupError::RubyTooLong { length, limit } => write!(
+                f,
+                "Ruby too long error: {} characters exceed the limit of {}",
+                length, limit
+            ),
+            MarkupError::DisallowedRubyCharacter(character) => write!(
+                f,
+                "Disallowed ruby character error: Character {} cannot be used in ruby",
+                character
+            ),
+            MarkupError::UnterminatedBlockComment { closing } => write!(
+                f,
+                "Unterminated block comment error: Block comment is not closed by {}",
+                closing
+            ),
+        }
+    }
+}
+
+/// Markup error with its position in source text
+///
+/// Both of line and column are counted from 1, and column is counted in characters.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub line: usize,
+    pub column: usize,
+    pub error: MarkupError,
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:{}: {}", self.line, self.column, self.error)
+    }
+}
@@ -0,0 +1,198 @@
+pub mod docx;
+pub mod epub;
+pub mod html;
+pub mod latex;
+
+use crate::errors::FormatParseError;
+use std::fmt;
+use std::str::FromStr;
+
+/// Output format of formatted text
+///
+/// Formats are selected below:
+/// * `narou` : Shosetsuka ni Naro(https://syosetu.com/)
+/// * `kakuyomu` : Kakuyomu(https://kakuyomu.jp/)
+/// * `aozora` : Aozora Bunko(https://www.aozora.gr.jp/) annotation
+/// * `pixiv` : pixiv novel(https://www.pixiv.net/novel/)
+/// * `html` : HTML document with `<ruby>` elements
+/// * `epub` : EPUB3 book
+/// * `latex` : LaTeX for upLaTeX or LuaLaTeX-ja
+/// * `docx` : Office Open XML document with native Word ruby
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Format {
+    #[default]
+    Narou,
+    Kakuyomu,
+    Aozora,
+    Pixiv,
+    Html,
+    Epub,
+    Latex,
+    Docx,
+}
+
+/// Implementation for output format
+impl Format {
+    /// Extension of a file saved in this format
+    ///
+    /// Return `None` if the format is plain text, so the source extension should be kept.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use naromat::format::Format;
+    ///
+    /// assert_eq!(Format::Narou.extension(), None);
+    /// assert_eq!(Format::Html.extension(), Some("html"));
+    /// ```
+    pub fn extension(self) -> Option<&'static str> {
+        match self {
+            Format::Narou | Format::Kakuyomu | Format::Aozora | Format::Pixiv => None,
+            Format::Html => Some("html"),
+            Format::Epub => Some("epub"),
+            Format::Latex => Some("tex"),
+            Format::Docx => Some("docx"),
+        }
+    }
+
+    /// Limits of ruby which the platform can render
+    ///
+    /// Return `None` if the format has no limit.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use naromat::format::Format;
+    ///
+    /// assert_eq!(Format::Narou.ruby_limits().unwrap().parent, 10);
+    /// assert!(Format::Html.ruby_limits().is_none());
+    /// ```
+    pub fn ruby_limits(self) -> Option<RubyLimits> {
+        match self {
+            Format::Narou => Some(RubyLimits {
+                parent: 10,
+                ruby: 10,
+                disallowed: &['《', '》', '｜', '|', '(', ')', '（', '）'],
+            }),
+            _ => None,
+        }
+    }
+}
+
+/// Limits of ruby which a platform can render
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RubyLimits {
+    /// Max characters of parent text
+    pub parent: usize,
+    /// Max characters of ruby text
+    pub ruby: usize,
+    /// Characters which cannot be used in parent or ruby text
+    pub disallowed: &'static [char],
+}
+
+/// Options of a formatted document
+///
+/// Options which a format does not support are ignored.
+#[derive(Debug, Clone, Default)]
+pub struct Options {
+    /// Title of the document
+    pub title: Option<String>,
+    /// Author of the document
+    pub author: Option<String>,
+    /// Write the document vertically (`writing-mode: vertical-rl`)
+    pub vertical: bool,
+    /// Wrap the document with preamble (LaTeX)
+    pub preamble: bool,
+    /// Save preface and afterword to the same file as body, separated by headings
+    pub combine_sections: bool,
+    /// Policy of empty lines between paragraphs
+    pub blank_lines: BlankLinePolicy,
+}
+
+/// Policy of empty lines between paragraphs in output
+///
+/// * `Preserve` - keep empty lines of source as is
+/// * `EveryParagraph` - insert an empty line between every pair of adjacent paragraphs
+/// * `Transition` - insert an empty line only between dialogue and narration
+/// * `Collapse(n)` - collapse runs of more than `n` empty lines to `n` empty lines
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BlankLinePolicy {
+    #[default]
+    Preserve,
+    EveryParagraph,
+    Transition,
+    Collapse(usize),
+}
+
+impl FromStr for Format {
+    type Err = FormatParseError;
+
+    /// Parse format name
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use naromat::format::Format;
+    ///
+    /// assert_eq!("kakuyomu".parse::<Format>().unwrap(), Format::Kakuyomu);
+    /// ```
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name.to_lowercase().as_str() {
+            "narou" => Ok(Format::Narou),
+            "kakuyomu" => Ok(Format::Kakuyomu),
+            "aozora" => Ok(Format::Aozora),
+            "pixiv" => Ok(Format::Pixiv),
+            "html" => Ok(Format::Html),
+            "epub" => Ok(Format::Epub),
+            "latex" => Ok(Format::Latex),
+            "docx" => Ok(Format::Docx),
+            _ => Err(FormatParseError::Unknown(name.to_string())),
+        }
+    }
+}
+
+impl fmt::Display for Format {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Format::Narou => write!(f, "narou"),
+            Format::Kakuyomu => write!(f, "kakuyomu"),
+            Format::Aozora => write!(f, "aozora"),
+            Format::Pixiv => write!(f, "pixiv"),
+            Format::Html => write!(f, "html"),
+            Format::Epub => write!(f, "epub"),
+            Format::Latex => write!(f, "latex"),
+            Format::Docx => write!(f, "docx"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Format;
+
+    #[test]
+    fn from_str_should_parse_known_format() {
+        assert_eq!("narou".parse::<Format>().unwrap(), Format::Narou);
+        assert_eq!("Kakuyomu".parse::<Format>().unwrap(), Format::Kakuyomu);
+        assert_eq!("aozora".parse::<Format>().unwrap(), Format::Aozora);
+        assert_eq!("pixiv".parse::<Format>().unwrap(), Format::Pixiv);
+        assert_eq!("HTML".parse::<Format>().unwrap(), Format::Html);
+        assert_eq!("epub".parse::<Format>().unwrap(), Format::Epub);
+        assert_eq!("latex".parse::<Format>().unwrap(), Format::Latex);
+        assert_eq!("docx".parse::<Format>().unwrap(), Format::Docx);
+    }
+
+    #[test]
+    fn from_str_should_return_error_when_unknown_format() {
+        assert!("unknown".parse::<Format>().is_err());
+    }
+
+    #[test]
+    fn display_should_be_parsable() {
+        assert_eq!(
+            Format::Kakuyomu.to_string().parse::<Format>().unwrap(),
+            Format::Kakuyomu
+        );
+    }
+}
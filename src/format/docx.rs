@@ -0,0 +1,289 @@
+use crate::entities::syntax::KentenStyle;
+use crate::format::html::escape;
+use crate::format::Options;
+use std::io::{Seek, Write};
+use zip::result::ZipResult;
+use zip::write::FileOptions;
+use zip::{CompressionMethod, ZipWriter};
+
+/// Run of plain text
+///
+/// # Example
+///
+/// ```
+/// use naromat::format::docx::run;
+///
+/// assert_eq!(run("猫"), "<w:r><w:t xml:space=\"preserve\">猫</w:t></w:r>");
+/// ```
+pub fn run(text: &str) -> String {
+    if text.is_empty() {
+        return String::new();
+    }
+    format!("<w:r><w:t xml:space=\"preserve\">{}</w:t></w:r>", text)
+}
+
+/// Run of text with emphasis mark(圏点)
+///
+/// # Example
+///
+/// ```
+/// use naromat::format::docx::emphasis;
+///
+/// assert_eq!(
+///     emphasis("猫"),
+///     "<w:r><w:rPr><w:em w:val=\"dot\"/></w:rPr><w:t xml:space=\"preserve\">猫</w:t></w:r>"
+/// );
+/// ```
+pub fn emphasis(text: &str) -> String {
+    emphasis_as(text, "dot")
+}
+
+/// Run of text with designated emphasis mark(`dot`, `comma`, `circle` or `underDot`)
+///
+/// # Example
+///
+/// ```
+/// use naromat::format::docx::emphasis_as;
+///
+/// assert!(emphasis_as("猫", "circle").contains("<w:em w:val=\"circle\"/>"));
+/// ```
+pub fn emphasis_as(text: &str, mark: &str) -> String {
+    format!(
+        "<w:r><w:rPr><w:em w:val=\"{}\"/></w:rPr><w:t xml:space=\"preserve\">{}</w:t></w:r>",
+        mark, text
+    )
+}
+
+/// Emphasis mark of Word which is the closest to designated kenten style
+pub(crate) fn emphasis_mark(style: KentenStyle) -> &'static str {
+    match style {
+        KentenStyle::Dot => "dot",
+        KentenStyle::Sesame => "comma",
+        KentenStyle::OpenCircle | KentenStyle::FilledCircle => "circle",
+    }
+}
+
+/// Run of text with ruby(ルビ)
+///
+/// # Example
+///
+/// ```
+/// use naromat::format::docx::ruby;
+///
+/// assert!(ruby("猫", "ねこ").contains("<w:rt><w:r><w:rPr><w:sz w:val=\"10\"/></w:rPr><w:t>ねこ</w:t></w:r></w:rt>"));
+/// ```
+pub fn ruby(base: &str, text: &str) -> String {
+    format!(
+        "<w:r><w:ruby>\
+         <w:rubyPr><w:rubyAlign w:val=\"distributeSpace\"/><w:hps w:val=\"10\"/><w:hpsRaise w:val=\"20\"/><w:hpsBaseText w:val=\"21\"/><w:lid w:val=\"ja-JP\"/></w:rubyPr>\
+         <w:rt><w:r><w:rPr><w:sz w:val=\"10\"/></w:rPr><w:t>{}</w:t></w:r></w:rt>\
+         <w:rubyBase><w:r><w:t>{}</w:t></w:r></w:rubyBase>\
+         </w:ruby></w:r>",
+        text, base
+    )
+}
+
+/// Paragraph of runs
+///
+/// A paragraph with indent has first-line indent of designated characters instead of literal `　`.
+///
+/// # Example
+///
+/// ```
+/// use naromat::format::docx::{paragraph, run};
+///
+/// assert_eq!(
+///     paragraph(&run("猫"), 1),
+///     "<w:p><w:pPr><w:ind w:firstLineChars=\"100\"/></w:pPr><w:r><w:t xml:space=\"preserve\">猫</w:t></w:r></w:p>"
+/// );
+/// assert_eq!(paragraph("", 0), "<w:p/>");
+/// ```
+pub fn paragraph(runs: &str, indent: usize) -> String {
+    if runs.is_empty() {
+        return "<w:p/>".to_string();
+    }
+    let properties = match indent {
+        0 => String::new(),
+        indent => format!("<w:pPr><w:ind w:firstLineChars=\"{}\"/></w:pPr>", indent * 100),
+    };
+    format!("<w:p>{}{}</w:p>", properties, runs)
+}
+
+/// Paragraph of runs which is centered without indent
+///
+/// # Example
+///
+/// ```
+/// use naromat::format::docx::{centered_paragraph, run};
+///
+/// assert_eq!(
+///     centered_paragraph(&run("†")),
+///     "<w:p><w:pPr><w:jc w:val=\"center\"/></w:pPr><w:r><w:t xml:space=\"preserve\">†</w:t></w:r></w:p>"
+/// );
+/// ```
+pub fn centered_paragraph(runs: &str) -> String {
+    format!("<w:p><w:pPr><w:jc w:val=\"center\"/></w:pPr>{}</w:p>", runs)
+}
+
+/// Paragraph of a page break
+///
+/// # Example
+///
+/// ```
+/// use naromat::format::docx::page_break;
+///
+/// assert_eq!(page_break(), "<w:p><w:r><w:br w:type=\"page\"/></w:r></w:p>");
+/// ```
+pub fn page_break() -> String {
+    "<w:p><w:r><w:br w:type=\"page\"/></w:r></w:p>".to_string()
+}
+
+/// Main document part(`word/document.xml`) which consists of paragraphs
+///
+/// # Example
+///
+/// ```
+/// use naromat::format::docx::document;
+/// use naromat::format::Options;
+///
+/// let xml = document("<w:p/>", &Options::default());
+/// assert!(xml.contains("<w:body>\n<w:p/>\n<w:sectPr"));
+/// ```
+pub fn document(body: &str, options: &Options) -> String {
+    let direction = if options.vertical {
+        "<w:textDirection w:val=\"tbRl\"/>"
+    } else {
+        ""
+    };
+    format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\n\
+         <w:document xmlns:w=\"http://schemas.openxmlformats.org/wordprocessingml/2006/main\">\n\
+         <w:body>\n{}\n<w:sectPr>{}</w:sectPr>\n</w:body>\n\
+         </w:document>\n",
+        body, direction
+    )
+}
+
+/// Write Office Open XML document which has the main document part
+///
+/// # Example
+///
+/// ```
+/// use naromat::format::docx::{document, write};
+/// use naromat::format::Options;
+/// use std::io::Cursor;
+///
+/// let mut docx = Cursor::new(Vec::new());
+/// write(&mut docx, &document("<w:p/>", &Options::default()), &Options::default()).unwrap();
+/// ```
+pub fn write<W: Write + Seek>(writer: W, document: &str, options: &Options) -> ZipResult<()> {
+    let mut zip = ZipWriter::new(writer);
+    let deflated = FileOptions::default().compression_method(CompressionMethod::Deflated);
+
+    zip.start_file("[Content_Types].xml", deflated)?;
+    zip.write_all(content_types().as_bytes())?;
+    zip.start_file("_rels/.rels", deflated)?;
+    zip.write_all(relationships().as_bytes())?;
+    zip.start_file("docProps/core.xml", deflated)?;
+    zip.write_all(core_properties(options).as_bytes())?;
+    zip.start_file("word/document.xml", deflated)?;
+    zip.write_all(document.as_bytes())?;
+    zip.finish()?;
+    Ok(())
+}
+
+/// Content types of parts in the package
+fn content_types() -> String {
+    "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\n\
+     <Types xmlns=\"http://schemas.openxmlformats.org/package/2006/content-types\">\n\
+     <Default Extension=\"rels\" ContentType=\"application/vnd.openxmlformats-package.relationships+xml\"/>\n\
+     <Default Extension=\"xml\" ContentType=\"application/xml\"/>\n\
+     <Override PartName=\"/word/document.xml\" ContentType=\"application/vnd.openxmlformats-officedocument.wordprocessingml.document.main+xml\"/>\n\
+     <Override PartName=\"/docProps/core.xml\" ContentType=\"application/vnd.openxmlformats-package.core-properties+xml\"/>\n\
+     </Types>\n"
+        .to_string()
+}
+
+/// Relationships from the package to the main document and properties
+fn relationships() -> String {
+    "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\n\
+     <Relationships xmlns=\"http://schemas.openxmlformats.org/package/2006/relationships\">\n\
+     <Relationship Id=\"rId1\" Type=\"http://schemas.openxmlformats.org/officeDocument/2006/relationships/officeDocument\" Target=\"word/document.xml\"/>\n\
+     <Relationship Id=\"rId2\" Type=\"http://schemas.openxmlformats.org/package/2006/relationships/metadata/core-properties\" Target=\"docProps/core.xml\"/>\n\
+     </Relationships>\n"
+        .to_string()
+}
+
+/// Core properties with title and author
+fn core_properties(options: &Options) -> String {
+    let title = match options.title {
+        Some(ref title) => format!("<dc:title>{}</dc:title>\n", escape(title)),
+        None => String::new(),
+    };
+    let creator = match options.author {
+        Some(ref author) => format!("<dc:creator>{}</dc:creator>\n", escape(author)),
+        None => String::new(),
+    };
+    format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\n\
+         <cp:coreProperties xmlns:cp=\"http://schemas.openxmlformats.org/package/2006/metadata/core-properties\" xmlns:dc=\"http://purl.org/dc/elements/1.1/\">\n\
+         {}{}\
+         </cp:coreProperties>\n",
+        title, creator
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{Cursor, Read};
+    use zip::ZipArchive;
+
+    #[test]
+    fn run_should_be_empty_when_text_is_empty() {
+        assert_eq!(run(""), "");
+    }
+
+    #[test]
+    fn paragraph_should_not_be_indented_when_indent_is_off() {
+        assert_eq!(paragraph(&run("「猫」"), 0), format!("<w:p>{}</w:p>", run("「猫」")));
+    }
+
+    #[test]
+    fn document_should_be_vertical_when_option_is_set() {
+        let options = Options {
+            vertical: true,
+            ..Options::default()
+        };
+        assert!(document("", &options).contains("<w:sectPr><w:textDirection w:val=\"tbRl\"/></w:sectPr>"));
+    }
+
+    #[test]
+    fn core_properties_should_have_escaped_metadata() {
+        let options = Options {
+            title: Some("猫<1>".to_string()),
+            author: Some("夏目".to_string()),
+            ..Options::default()
+        };
+        let properties = core_properties(&options);
+        assert!(properties.contains("<dc:title>猫&lt;1&gt;</dc:title>"));
+        assert!(properties.contains("<dc:creator>夏目</dc:creator>"));
+    }
+
+    #[test]
+    fn write_should_contain_main_document() {
+        let mut docx = Cursor::new(Vec::new());
+        write(&mut docx, "<w:document/>", &Options::default()).unwrap();
+
+        let mut archive = ZipArchive::new(docx).unwrap();
+        let mut document = String::new();
+        archive
+            .by_name("word/document.xml")
+            .unwrap()
+            .read_to_string(&mut document)
+            .unwrap();
+        assert_eq!(document, "<w:document/>");
+        assert!(archive.by_name("[Content_Types].xml").is_ok());
+        assert!(archive.by_name("_rels/.rels").is_ok());
+    }
+}
@@ -0,0 +1,329 @@
+use crate::format::html::{escape, KENTEN_STYLE, SCENE_BREAK_STYLE, TATE_CHU_YOKO_STYLE};
+use crate::format::Options;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::io::{Seek, Write};
+use std::time::{SystemTime, UNIX_EPOCH};
+use zip::result::ZipResult;
+use zip::write::FileOptions;
+use zip::{CompressionMethod, ZipWriter};
+
+/// Content document of EPUB3 book
+pub struct ContentDocument {
+    /// Title of the document, which is shown in navigation
+    pub title: String,
+    /// XHTML of the document
+    pub xhtml: String,
+}
+
+/// Wrap XHTML body to a complete EPUB3 content document
+///
+/// # Example
+///
+/// ```
+/// use naromat::format::epub::content_document;
+///
+/// let xhtml = content_document("第一話", "<p>　我が輩は猫である。</p>");
+/// assert!(xhtml.contains("<title>第一話</title>"));
+/// assert!(xhtml.contains("<body>\n<p>　我が輩は猫である。</p>\n</body>"));
+/// ```
+pub fn content_document(title: &str, body: &str) -> String {
+    format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+         <!DOCTYPE html>\n\
+         <html xmlns=\"http://www.w3.org/1999/xhtml\" xmlns:epub=\"http://www.idpf.org/2007/ops\" xml:lang=\"ja\" lang=\"ja\">\n\
+         <head>\n\
+         <meta charset=\"UTF-8\" />\n\
+         <title>{}</title>\n\
+         <link rel=\"stylesheet\" type=\"text/css\" href=\"../style.css\" />\n\
+         </head>\n\
+         <body>\n{}\n</body>\n\
+         </html>\n",
+        escape(title),
+        body
+    )
+}
+
+/// Write EPUB3 book which consists of content documents
+///
+/// # Example
+///
+/// ```
+/// use naromat::format::epub::{write, ContentDocument};
+/// use naromat::format::Options;
+/// use std::io::Cursor;
+///
+/// let documents = vec![ContentDocument {
+///     title: "第一話".to_string(),
+///     xhtml: "<p>　我が輩は猫である。</p>".to_string(),
+/// }];
+/// let mut book = Cursor::new(Vec::new());
+/// write(&mut book, &documents, &Options::default()).unwrap();
+/// ```
+pub fn write<W: Write + Seek>(writer: W, documents: &[ContentDocument], options: &Options) -> ZipResult<()> {
+    let mut zip = ZipWriter::new(writer);
+    let stored = FileOptions::default().compression_method(CompressionMethod::Stored);
+    let deflated = FileOptions::default().compression_method(CompressionMethod::Deflated);
+
+    zip.start_file("mimetype", stored)?;
+    zip.write_all(b"application/epub+zip")?;
+    zip.start_file("META-INF/container.xml", deflated)?;
+    zip.write_all(container().as_bytes())?;
+    zip.start_file("OEBPS/content.opf", deflated)?;
+    zip.write_all(package(documents, options).as_bytes())?;
+    zip.start_file("OEBPS/nav.xhtml", deflated)?;
+    zip.write_all(navigation(documents).as_bytes())?;
+    zip.start_file("OEBPS/style.css", deflated)?;
+    zip.write_all(style(options).as_bytes())?;
+    for (index, document) in documents.iter().enumerate() {
+        zip.start_file(format!("OEBPS/{}", document_path(index)), deflated)?;
+        zip.write_all(document.xhtml.as_bytes())?;
+    }
+    zip.finish()?;
+    Ok(())
+}
+
+/// Path of a content document from the package document
+fn document_path(index: usize) -> String {
+    format!("text/episode-{:04}.xhtml", index + 1)
+}
+
+/// Container file which points the package document
+fn container() -> String {
+    "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+     <container version=\"1.0\" xmlns=\"urn:oasis:names:tc:opendocument:xmlns:container\">\n\
+     <rootfiles>\n\
+     <rootfile full-path=\"OEBPS/content.opf\" media-type=\"application/oebps-package+xml\" />\n\
+     </rootfiles>\n\
+     </container>\n"
+        .to_string()
+}
+
+/// Package document with metadata, manifest and spine
+fn package(documents: &[ContentDocument], options: &Options) -> String {
+    let title = options.title.clone().unwrap_or_default();
+    let creator = match options.author {
+        Some(ref author) => format!("<dc:creator>{}</dc:creator>\n", escape(author)),
+        None => String::new(),
+    };
+    let manifest: String = (0..documents.len())
+        .map(|index| {
+            format!(
+                "<item id=\"episode-{:04}\" href=\"{}\" media-type=\"application/xhtml+xml\" />\n",
+                index + 1,
+                document_path(index)
+            )
+        })
+        .collect();
+    let spine: String = (0..documents.len())
+        .map(|index| format!("<itemref idref=\"episode-{:04}\" />\n", index + 1))
+        .collect();
+    let direction = if options.vertical {
+        " page-progression-direction=\"rtl\""
+    } else {
+        ""
+    };
+    format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+         <package xmlns=\"http://www.idpf.org/2007/opf\" version=\"3.0\" unique-identifier=\"book-id\" xml:lang=\"ja\">\n\
+         <metadata xmlns:dc=\"http://purl.org/dc/elements/1.1/\">\n\
+         <dc:identifier id=\"book-id\">{}</dc:identifier>\n\
+         <dc:title>{}</dc:title>\n\
+         {}\
+         <dc:language>ja</dc:language>\n\
+         <meta property=\"dcterms:modified\">{}</meta>\n\
+         </metadata>\n\
+         <manifest>\n\
+         <item id=\"nav\" href=\"nav.xhtml\" media-type=\"application/xhtml+xml\" properties=\"nav\" />\n\
+         <item id=\"style\" href=\"style.css\" media-type=\"text/css\" />\n\
+         {}\
+         </manifest>\n\
+         <spine{}>\n\
+         {}\
+         </spine>\n\
+         </package>\n",
+        identifier(documents, options),
+        escape(&title),
+        creator,
+        modified(SystemTime::now()),
+        manifest,
+        direction,
+        spine
+    )
+}
+
+/// Navigation document which lists content documents
+fn navigation(documents: &[ContentDocument]) -> String {
+    let items: String = documents
+        .iter()
+        .enumerate()
+        .map(|(index, document)| {
+            format!(
+                "<li><a href=\"{}\">{}</a></li>\n",
+                document_path(index),
+                escape(&document.title)
+            )
+        })
+        .collect();
+    format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+         <!DOCTYPE html>\n\
+         <html xmlns=\"http://www.w3.org/1999/xhtml\" xmlns:epub=\"http://www.idpf.org/2007/ops\" xml:lang=\"ja\" lang=\"ja\">\n\
+         <head>\n\
+         <meta charset=\"UTF-8\" />\n\
+         <title>目次</title>\n\
+         </head>\n\
+         <body>\n\
+         <nav epub:type=\"toc\" id=\"toc\">\n\
+         <h1>目次</h1>\n\
+         <ol>\n\
+         {}\
+         </ol>\n\
+         </nav>\n\
+         </body>\n\
+         </html>\n",
+        items
+    )
+}
+
+/// Style sheet shared by content documents
+fn style(options: &Options) -> String {
+    let mut style = String::new();
+    if options.vertical {
+        style.push_str("html { -epub-writing-mode: vertical-rl; writing-mode: vertical-rl; }\n");
+    }
+    style.push_str(KENTEN_STYLE);
+    style.push_str(SCENE_BREAK_STYLE);
+    style.push_str(TATE_CHU_YOKO_STYLE);
+    style
+}
+
+/// Unique identifier of a book derived from its metadata and contents
+fn identifier(documents: &[ContentDocument], options: &Options) -> String {
+    let mut hasher = DefaultHasher::new();
+    options.title.hash(&mut hasher);
+    options.author.hash(&mut hasher);
+    for document in documents {
+        document.title.hash(&mut hasher);
+        document.xhtml.hash(&mut hasher);
+    }
+    format!("urn:naromat:{:016x}", hasher.finish())
+}
+
+/// Modified date in `CCYY-MM-DDThh:mm:ssZ`
+fn modified(time: SystemTime) -> String {
+    let seconds = time.duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+    let (days, seconds) = (seconds / 86400, seconds % 86400);
+    // Convert days from 1970-01-01 to civil date (proleptic Gregorian calendar)
+    let days = days as i64 + 719_468;
+    let era = days / 146_097;
+    let day_of_era = days - era * 146_097;
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year,
+        month,
+        day,
+        seconds / 3600,
+        seconds % 3600 / 60,
+        seconds % 60
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{Cursor, Read};
+    use std::time::Duration;
+    use zip::ZipArchive;
+
+    fn documents() -> Vec<ContentDocument> {
+        vec![
+            ContentDocument {
+                title: "第一話".to_string(),
+                xhtml: content_document("第一話", "<p>　一</p>"),
+            },
+            ContentDocument {
+                title: "第二話 <前編>".to_string(),
+                xhtml: content_document("第二話 <前編>", "<p>　二</p>"),
+            },
+        ]
+    }
+
+    #[test]
+    fn write_should_store_mimetype_first_without_compression() {
+        let mut book = Cursor::new(Vec::new());
+        write(&mut book, &documents(), &Options::default()).unwrap();
+
+        let mut archive = ZipArchive::new(book).unwrap();
+        let mut mimetype = archive.by_index(0).unwrap();
+        let mut content = String::new();
+        mimetype.read_to_string(&mut content).unwrap();
+        assert_eq!(mimetype.name(), "mimetype");
+        assert_eq!(mimetype.compression(), CompressionMethod::Stored);
+        assert_eq!(content, "application/epub+zip");
+    }
+
+    #[test]
+    fn write_should_contain_every_document() {
+        let mut book = Cursor::new(Vec::new());
+        write(&mut book, &documents(), &Options::default()).unwrap();
+
+        let mut archive = ZipArchive::new(book).unwrap();
+        let mut second = String::new();
+        archive
+            .by_name("OEBPS/text/episode-0002.xhtml")
+            .unwrap()
+            .read_to_string(&mut second)
+            .unwrap();
+        assert!(second.contains("<p>　二</p>"));
+        assert!(archive.by_name("OEBPS/nav.xhtml").is_ok());
+        assert!(archive.by_name("META-INF/container.xml").is_ok());
+    }
+
+    #[test]
+    fn package_should_have_metadata() {
+        let options = Options {
+            title: Some("猫 & 犬".to_string()),
+            author: Some("夏目".to_string()),
+            vertical: true,
+            ..Options::default()
+        };
+        let package = package(&documents(), &options);
+        assert!(package.contains("<dc:title>猫 &amp; 犬</dc:title>"));
+        assert!(package.contains("<dc:creator>夏目</dc:creator>"));
+        assert!(package.contains("<spine page-progression-direction=\"rtl\">"));
+        assert!(package.contains("<itemref idref=\"episode-0002\" />"));
+    }
+
+    #[test]
+    fn navigation_should_list_escaped_titles() {
+        let navigation = navigation(&documents());
+        assert!(navigation.contains("<li><a href=\"text/episode-0002.xhtml\">第二話 &lt;前編&gt;</a></li>"));
+    }
+
+    #[test]
+    fn style_should_be_vertical_when_option_is_set() {
+        let options = Options {
+            vertical: true,
+            ..Options::default()
+        };
+        assert!(style(&options).contains("writing-mode: vertical-rl;"));
+        assert!(!style(&Options::default()).contains("writing-mode"));
+    }
+
+    #[test]
+    fn modified_should_format_date() {
+        let time = UNIX_EPOCH + Duration::from_secs(1_617_580_800 + 3661);
+        assert_eq!(modified(time), "2021-04-05T01:01:01Z");
+    }
+}
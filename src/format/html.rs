@@ -0,0 +1,126 @@
+use crate::entities::syntax::KentenStyle;
+use crate::format::Options;
+
+/// Escape characters which have special meaning in HTML
+///
+/// # Example
+///
+/// ```
+/// use naromat::format::html::escape;
+///
+/// assert_eq!(escape("<猫> & 犬"), "&lt;猫&gt; &amp; 犬");
+/// ```
+pub fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+/// Wrap HTML body to a complete document
+///
+/// # Example
+///
+/// ```
+/// use naromat::format::html::document;
+/// use naromat::format::Options;
+///
+/// let options = Options {
+///     vertical: true,
+///     ..Options::default()
+/// };
+/// let html = document("<p>　我が輩は猫である。</p>", &options);
+/// assert!(html.contains("writing-mode: vertical-rl;"));
+/// assert!(html.contains("<body>\n<p>　我が輩は猫である。</p>\n</body>"));
+/// ```
+pub fn document(body: &str, options: &Options) -> String {
+    let title = match options.title {
+        Some(ref title) => format!("<title>{}</title>\n", escape(title)),
+        None => String::new(),
+    };
+    format!(
+        "<!DOCTYPE html>\n<html lang=\"ja\">\n<head>\n<meta charset=\"utf-8\" />\n{}<style>\n{}</style>\n</head>\n<body>\n{}\n</body>\n</html>",
+        title,
+        style(options),
+        body
+    )
+}
+
+/// Style sheet of a document
+fn style(options: &Options) -> String {
+    let mut style = String::new();
+    if options.vertical {
+        style.push_str("body { writing-mode: vertical-rl; }\n");
+    }
+    style.push_str(KENTEN_STYLE);
+    style.push_str(SCENE_BREAK_STYLE);
+    style.push_str(TATE_CHU_YOKO_STYLE);
+    style
+}
+
+/// Class of kenten(圏点) which designates style of marks in addition to `kenten`
+///
+/// Return `None` for dots, which is the style of `kenten` class itself.
+pub(crate) fn kenten_class(style: KentenStyle) -> Option<&'static str> {
+    match style {
+        KentenStyle::Dot => None,
+        KentenStyle::Sesame => Some("sesame"),
+        KentenStyle::OpenCircle => Some("open-circle"),
+        KentenStyle::FilledCircle => Some("filled-circle"),
+    }
+}
+
+/// Style of kenten(圏点) which is rendered as `<em class="kenten">`
+pub(crate) const KENTEN_STYLE: &str = "\
+em.kenten { font-style: normal; -webkit-text-emphasis: filled dot; text-emphasis: filled dot; }
+em.kenten.sesame { -webkit-text-emphasis-style: filled sesame; text-emphasis-style: filled sesame; }
+em.kenten.open-circle { -webkit-text-emphasis-style: open circle; text-emphasis-style: open circle; }
+em.kenten.filled-circle { -webkit-text-emphasis-style: filled circle; text-emphasis-style: filled circle; }
+";
+
+/// Style of digits set horizontally in vertical text(`<span class="tcy">`)
+pub(crate) const TATE_CHU_YOKO_STYLE: &str = "\
+span.tcy { -webkit-text-combine: horizontal; text-combine-upright: all; }
+";
+
+/// Style of scene breaks(`<p class="scene-break">`) and page breaks(`<hr class="page-break" />`)
+pub(crate) const SCENE_BREAK_STYLE: &str = "\
+p.scene-break { text-indent: 0; text-align: center; margin: 1em 0; }
+hr.page-break { border: none; page-break-after: always; break-after: page; }
+";
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escape_should_escape_special_characters() {
+        assert_eq!(escape("a<b>c&d"), "a&lt;b&gt;c&amp;d");
+    }
+
+    #[test]
+    fn escape_should_escape_ampersand_of_entity() {
+        assert_eq!(escape("&amp;"), "&amp;amp;");
+    }
+
+    #[test]
+    fn document_should_be_horizontal_by_default() {
+        let html = document("", &Options::default());
+        assert!(!html.contains("writing-mode"));
+        assert!(!html.contains("<title>"));
+    }
+
+    #[test]
+    fn document_should_have_style_of_every_kenten_class() {
+        let html = document("", &Options::default());
+        for style in &[KentenStyle::Sesame, KentenStyle::OpenCircle, KentenStyle::FilledCircle] {
+            assert!(html.contains(&format!("em.kenten.{} {{", kenten_class(*style).unwrap())));
+        }
+    }
+
+    #[test]
+    fn document_should_have_escaped_title() {
+        let options = Options {
+            title: Some("猫<1>".to_string()),
+            ..Options::default()
+        };
+        assert!(document("", &options).contains("<title>猫&lt;1&gt;</title>"));
+    }
+}
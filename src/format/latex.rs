@@ -0,0 +1,98 @@
+use crate::format::Options;
+
+/// Escape characters which have special meaning in LaTeX
+///
+/// # Example
+///
+/// ```
+/// use naromat::format::latex::escape;
+///
+/// assert_eq!(escape("100% {猫} & 犬_1"), r"100\% \{猫\} \& 犬\_1");
+/// ```
+pub fn escape(text: &str) -> String {
+    let mut escaped = String::new();
+    for character in text.chars() {
+        match character {
+            '\\' => escaped.push_str(r"\textbackslash{}"),
+            '^' => escaped.push_str(r"\textasciicircum{}"),
+            '~' => escaped.push_str(r"\textasciitilde{}"),
+            '#' | '%' | '&' | '_' | '{' | '}' | '$' => {
+                escaped.push('\\');
+                escaped.push(character);
+            }
+            _ => escaped.push(character),
+        }
+    }
+    escaped
+}
+
+/// Wrap LaTeX body to a complete document for upLaTeX or LuaLaTeX-ja
+///
+/// Ruby and kenten are typeset by `pxrubrica` package, and `\scenebreak` is defined to center a scene break marker.
+///
+/// # Example
+///
+/// ```
+/// use naromat::format::latex::document;
+/// use naromat::format::Options;
+///
+/// let options = Options {
+///     title: Some("吾輩は猫である".to_string()),
+///     vertical: true,
+///     ..Options::default()
+/// };
+/// let latex = document("\\noindent　我が輩は猫である。", &options);
+/// assert!(latex.starts_with("\\documentclass[tate]{jlreq}"));
+/// assert!(latex.contains("\\title{吾輩は猫である}"));
+/// assert!(latex.ends_with("\\noindent　我が輩は猫である。\n\\end{document}\n"));
+/// ```
+pub fn document(body: &str, options: &Options) -> String {
+    let class_option = if options.vertical { "[tate]" } else { "" };
+    let mut preamble = format!(
+        "\\documentclass{}{{jlreq}}\n\\usepackage{{pxrubrica}}\n{}",
+        class_option, SCENE_BREAK_COMMAND
+    );
+    if let Some(ref title) = options.title {
+        preamble.push_str(&format!("\\title{{{}}}\n", escape(title)));
+    }
+    if let Some(ref author) = options.author {
+        preamble.push_str(&format!("\\author{{{}}}\n", escape(author)));
+    }
+    let title = if options.title.is_some() { "\\maketitle\n" } else { "" };
+    format!(
+        "{}\\begin{{document}}\n{}{}\n\\end{{document}}\n",
+        preamble, title, body
+    )
+}
+
+/// Definition of `\scenebreak{marker}` which centers a scene break marker
+const SCENE_BREAK_COMMAND: &str = "\\providecommand{\\scenebreak}[1]{\\par\\bigskip{\\centering #1\\par}\\bigskip}\n";
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escape_should_escape_special_characters() {
+        assert_eq!(
+            escape(r"#%&_{}\$^~"),
+            r"\#\%\&\_\{\}\textbackslash{}\$\textasciicircum{}\textasciitilde{}"
+        );
+    }
+
+    #[test]
+    fn document_should_be_horizontal_by_default() {
+        let latex = document("", &Options::default());
+        assert!(latex.starts_with("\\documentclass{jlreq}\n\\usepackage{pxrubrica}\n\\providecommand{\\scenebreak}"));
+        assert!(!latex.contains("\\maketitle"));
+    }
+
+    #[test]
+    fn document_should_have_escaped_author() {
+        let options = Options {
+            author: Some("A&B".to_string()),
+            ..Options::default()
+        };
+        assert!(document("", &options).contains("\\author{A\\&B}"));
+    }
+}
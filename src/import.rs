@@ -0,0 +1,14 @@
+pub mod aozora;
+pub mod docx;
+pub mod narou;
+
+/// Notations which source text can be written in
+///
+/// * `Naromat` - naromat source notation(`[親:ルビ]`, `[強調:.]`, `// コメント`)
+/// * `Aozora` - Aozora Bunko notation(`｜親《ルビ》`, `［＃「強調」に傍点］`), see `naromat::import::aozora::import`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Dialect {
+    #[default]
+    Naromat,
+    Aozora,
+}
@@ -0,0 +1,183 @@
+use crate::entities::line::Line;
+use crate::entities::syntax;
+use crate::import::narou;
+use regex::Regex;
+
+/// Import text written in Aozora Bunko notation as lines of naromat source
+///
+/// Each line is returned with its indent width in characters.
+///
+/// * `｜漢字《かんじ》` and `漢字《かんじ》` become `[漢字:かんじ]`
+/// * `語［＃「語」に傍点］` becomes `[語:.]`, and `丸傍点` or `白丸傍点` becomes `[語:.●]` or `[語:.o]`
+/// * `［＃ここから２字下げ］` indents following lines until `［＃ここで字下げ終わり］`
+/// * `［＃２字下げ］` at line head indents the line
+/// * `［＃改ページ］` becomes a page break(`@newpage`)
+/// * Other annotations become comments(`[#注記]`)
+/// * Brackets and `//` at line head are escaped
+///
+/// # Example
+///
+/// ```
+/// use naromat::import::aozora::import;
+///
+/// let lines = import("［＃ここから２字下げ］\n吾輩《わがはい》は猫［＃「猫」に傍点］である。\n［＃ここで字下げ終わり］");
+/// assert_eq!(lines, vec![(2, "[吾輩:わがはい]は[猫:.]である。".to_string())]);
+/// ```
+pub fn import(text: &str) -> Vec<(usize, String)> {
+    let block_start = Regex::new(r"^［＃ここから([0-9０-９]+)字下げ[^］]*］$").unwrap();
+    let block_end = Regex::new(r"^［＃ここで字下げ終わり］$").unwrap();
+    let page_break = Regex::new(r"^［＃改(ページ|丁|段|見開き)］$").unwrap();
+    let line_indent = Regex::new(r"^［＃([0-9０-９]+)字下げ］").unwrap();
+
+    let mut block_indent = 0;
+    let mut lines = Vec::new();
+    for line in text.split_terminator('\n') {
+        let line = line.trim_end_matches('\r').trim();
+        if let Some(caps) = block_start.captures(line) {
+            block_indent = parse_number(&caps[1]);
+            continue;
+        }
+        if block_end.is_match(line) {
+            block_indent = 0;
+            continue;
+        }
+        if page_break.is_match(line) {
+            lines.push((0, "@newpage".to_string()));
+            continue;
+        }
+        let (indent, line) = match line_indent.captures(line) {
+            Some(caps) => (parse_number(&caps[1]), &line[caps[0].len()..]),
+            None => (block_indent, line),
+        };
+        lines.push((indent, import_line(line)));
+    }
+    lines
+}
+
+/// Import inline notations of a line
+fn import_line(line: &str) -> String {
+    let line = Line::escape_comment_head(&syntax::escape(line));
+    let line = convert_annotation(&line);
+    let line = narou::convert_explicit_ruby(&line);
+    narou::convert_implicit_ruby(&line)
+}
+
+/// Convert kenten annotations, and other annotations to comments
+fn convert_annotation(line: &str) -> String {
+    let annotation = Regex::new(r"［＃([^］]*)］").unwrap();
+    let kenten = Regex::new(r"^「([^」]+)」に([^」]*)傍点$").unwrap();
+
+    let mut converted = String::new();
+    let mut last = 0;
+    for caps in annotation.captures_iter(line) {
+        let whole = caps.get(0).unwrap();
+        converted.push_str(&line[last..whole.start()]);
+        last = whole.end();
+        match kenten.captures(&caps[1]) {
+            Some(target) if converted.ends_with(&target[1]) => {
+                converted.truncate(converted.len() - target[1].len());
+                let style = match &target[2] {
+                    "丸" => ".●",
+                    "白丸" => ".o",
+                    _ => ".",
+                };
+                converted.push_str(&format!("[{}:{}]", narou::escape_markup(&target[1]), style));
+            }
+            _ => converted.push_str(&format!("[#{}]", &caps[1])),
+        }
+    }
+    converted.push_str(&line[last..]);
+    converted
+}
+
+/// Parse number written in half or full width digits
+fn parse_number(digits: &str) -> usize {
+    digits
+        .chars()
+        .filter_map(|digit| match digit {
+            '０'..='９' => Some(digit as u32 - '０' as u32),
+            _ => digit.to_digit(10),
+        })
+        .fold(0, |number, digit| number * 10 + digit as usize)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn import_should_convert_ruby() {
+        assert_eq!(
+            import("｜東京《とうきょう》の人々《ひとびと》"),
+            vec![(0, "[東京:とうきょう]の[人々:ひとびと]".to_string())]
+        );
+    }
+
+    #[test]
+    fn import_should_convert_page_break_to_page_marker() {
+        assert_eq!(
+            import("猫\n［＃改ページ］\n犬"),
+            vec![
+                (0, "猫".to_string()),
+                (0, "@newpage".to_string()),
+                (0, "犬".to_string())
+            ]
+        );
+    }
+
+    #[test]
+    fn import_should_escape_brackets() {
+        assert_eq!(import("[HP:100]"), vec![(0, r"\[HP:100\]".to_string())]);
+    }
+
+    #[test]
+    fn import_should_indent_a_line() {
+        assert_eq!(import("［＃３字下げ］猫"), vec![(3, "猫".to_string())]);
+    }
+
+    #[test]
+    fn import_should_indent_lines_in_block() {
+        assert_eq!(
+            import("［＃ここから１０字下げ］\n猫\n犬\n［＃ここで字下げ終わり］\n鳥"),
+            vec![(10, "猫".to_string()), (10, "犬".to_string()), (0, "鳥".to_string())]
+        );
+    }
+
+    #[test]
+    fn convert_annotation_should_convert_kenten_of_preceding_text() {
+        assert_eq!(
+            convert_annotation("名前はまだ［＃「まだ」に白ゴマ傍点］ない"),
+            "名前は[まだ:.]ない"
+        );
+    }
+
+    #[test]
+    fn convert_annotation_should_convert_circle_kenten_style() {
+        assert_eq!(
+            convert_annotation("猫［＃「猫」に丸傍点］と犬［＃「犬」に白丸傍点］"),
+            "[猫:.●]と[犬:.o]"
+        );
+    }
+
+    #[test]
+    fn convert_annotation_should_keep_kenten_for_other_text_as_comment() {
+        assert_eq!(
+            convert_annotation("名前はない［＃「まだ」に傍点］"),
+            "名前はない[#「まだ」に傍点]"
+        );
+    }
+
+    #[test]
+    fn convert_annotation_should_convert_unknown_annotation_to_comment() {
+        assert_eq!(
+            convert_annotation("第一章［＃「第一章」は大見出し］"),
+            "第一章[#「第一章」は大見出し]"
+        );
+    }
+
+    #[test]
+    fn parse_number_should_parse_full_width_digits() {
+        assert_eq!(parse_number("１２"), 12);
+        assert_eq!(parse_number("3"), 3);
+    }
+}
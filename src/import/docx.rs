@@ -0,0 +1,318 @@
+use crate::entities::line::Line;
+use crate::entities::syntax;
+use crate::errors::ImportError;
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+use std::collections::HashMap;
+use std::io::{Read, Seek};
+use zip::ZipArchive;
+
+/// Import Word document(.docx) as naromat source text
+///
+/// * `w:ruby` runs become `[親:ルビ]`
+/// * Runs with emphasis mark(`w:em`) become `[強調:.]`
+/// * Comments become inline comments `[#...]`
+/// * Brackets and `//` at line head in texts are escaped
+///
+/// # Example
+///
+/// ```no_run
+/// use naromat::import::docx::import;
+/// use std::fs::File;
+///
+/// let source = import(File::open("./path/to/manuscript.docx").unwrap()).unwrap();
+/// ```
+pub fn import<R: Read + Seek>(reader: R) -> Result<String, ImportError> {
+    let mut archive = ZipArchive::new(reader).map_err(ImportError::InvalidPackage)?;
+    let comments = match read_part(&mut archive, "word/comments.xml") {
+        Ok(xml) => parse_comments(&xml)?,
+        Err(_) => HashMap::new(),
+    };
+    let document = read_part(&mut archive, "word/document.xml")?;
+    parse_document(&document, &comments)
+}
+
+/// Read a part of the package as string
+fn read_part<R: Read + Seek>(archive: &mut ZipArchive<R>, name: &str) -> Result<String, ImportError> {
+    let mut part = archive.by_name(name).map_err(ImportError::InvalidPackage)?;
+    let mut xml = String::new();
+    part.read_to_string(&mut xml).map_err(ImportError::CannotRead)?;
+    Ok(xml)
+}
+
+/// Local name of an element or an attribute(`w:id` -> `id`)
+fn local_name(name: &[u8]) -> &[u8] {
+    match name.iter().rposition(|byte| *byte == b':') {
+        Some(position) => &name[position + 1..],
+        None => name,
+    }
+}
+
+/// Value of an attribute designated by local name
+fn attribute(element: &BytesStart, reader: &Reader<&[u8]>, name: &[u8]) -> Result<Option<String>, ImportError> {
+    for attribute in element.attributes() {
+        let attribute = attribute.map_err(|error| ImportError::InvalidXml(error.into()))?;
+        if local_name(attribute.key) == name {
+            let value = attribute
+                .unescape_and_decode_value(reader)
+                .map_err(ImportError::InvalidXml)?;
+            return Ok(Some(value));
+        }
+    }
+    Ok(None)
+}
+
+/// Collect texts of comments(`word/comments.xml`) by id
+fn parse_comments(xml: &str) -> Result<HashMap<String, String>, ImportError> {
+    let mut reader = Reader::from_str(xml);
+    let mut buffer = Vec::new();
+    let mut comments = HashMap::new();
+    let mut id = None;
+    let mut in_text = false;
+    loop {
+        match reader.read_event(&mut buffer).map_err(ImportError::InvalidXml)? {
+            Event::Start(ref element) => match local_name(element.name()) {
+                b"comment" => {
+                    id = attribute(element, &reader, b"id")?;
+                    if let Some(ref id) = id {
+                        comments.insert(id.clone(), String::new());
+                    }
+                }
+                b"t" => in_text = true,
+                _ => (),
+            },
+            Event::End(ref element) => match local_name(element.name()) {
+                b"comment" => id = None,
+                b"t" => in_text = false,
+                _ => (),
+            },
+            Event::Text(ref text) if in_text => {
+                if let Some(comment) = id.as_ref().and_then(|id| comments.get_mut(id)) {
+                    let text = text.unescape_and_decode(&reader).map_err(ImportError::InvalidXml)?;
+                    comment.push_str(&syntax::escape(&text));
+                }
+            }
+            Event::Eof => break,
+            _ => (),
+        }
+        buffer.clear();
+    }
+    Ok(comments)
+}
+
+/// State of the main document while it is parsed
+#[derive(Default)]
+struct Document {
+    source: String,
+    emphasis: String,
+    ruby_base: String,
+    ruby_text: String,
+    in_run: bool,
+    in_text: bool,
+    in_ruby: bool,
+    in_ruby_base: bool,
+    in_ruby_text: bool,
+    is_emphasized: bool,
+}
+
+impl Document {
+    /// Append text of a run
+    fn push_text(&mut self, text: &str) {
+        if self.in_ruby_text {
+            self.ruby_text.push_str(text);
+        } else if self.in_ruby_base {
+            self.ruby_base.push_str(text);
+        } else if self.is_emphasized {
+            self.emphasis.push_str(text);
+        } else {
+            self.flush_emphasis();
+            self.source.push_str(text);
+        }
+    }
+
+    /// Append emphasized texts as kenten
+    fn flush_emphasis(&mut self) {
+        if !self.emphasis.is_empty() {
+            self.source.push_str(&format!("[{}:.]", self.emphasis));
+            self.emphasis.clear();
+        }
+    }
+
+    /// Append a piece of source which is not text
+    fn push_source(&mut self, source: &str) {
+        self.flush_emphasis();
+        self.source.push_str(source);
+    }
+}
+
+/// Convert the main document(`word/document.xml`) to naromat source text
+fn parse_document(xml: &str, comments: &HashMap<String, String>) -> Result<String, ImportError> {
+    let mut reader = Reader::from_str(xml);
+    let mut buffer = Vec::new();
+    let mut document = Document::default();
+    loop {
+        match reader.read_event(&mut buffer).map_err(ImportError::InvalidXml)? {
+            Event::Start(ref element) => match local_name(element.name()) {
+                b"r" if !document.in_ruby => {
+                    document.in_run = true;
+                    document.is_emphasized = false;
+                }
+                b"t" => document.in_text = true,
+                b"ruby" => {
+                    document.flush_emphasis();
+                    document.in_ruby = true;
+                }
+                b"rt" => document.in_ruby_text = true,
+                b"rubyBase" => document.in_ruby_base = true,
+                _ => (),
+            },
+            Event::Empty(ref element) => match local_name(element.name()) {
+                b"em" if document.in_run && !document.in_ruby => {
+                    let mark = attribute(element, &reader, b"val")?;
+                    document.is_emphasized = mark.is_none_or(|mark| mark != "none");
+                }
+                b"tab" if document.in_run => document.push_text("\t"),
+                b"br" if document.in_run => document.push_source("\n"),
+                b"commentReference" => {
+                    let id = attribute(element, &reader, b"id")?.unwrap_or_default();
+                    if let Some(comment) = comments.get(&id) {
+                        document.push_source(&format!("[#{}]", comment));
+                    }
+                }
+                b"p" => document.push_source("\n"),
+                _ => (),
+            },
+            Event::End(ref element) => match local_name(element.name()) {
+                b"p" => document.push_source("\n"),
+                b"r" if !document.in_ruby => {
+                    document.in_run = false;
+                    document.is_emphasized = false;
+                }
+                b"t" => document.in_text = false,
+                b"ruby" => {
+                    let ruby = format!("[{}:{}]", document.ruby_base, document.ruby_text);
+                    document.ruby_base.clear();
+                    document.ruby_text.clear();
+                    document.in_ruby = false;
+                    document.push_source(&ruby);
+                }
+                b"rt" => document.in_ruby_text = false,
+                b"rubyBase" => document.in_ruby_base = false,
+                _ => (),
+            },
+            Event::Text(ref text) if document.in_text => {
+                let text = text.unescape_and_decode(&reader).map_err(ImportError::InvalidXml)?;
+                document.push_text(&syntax::escape(&text));
+            }
+            Event::Eof => break,
+            _ => (),
+        }
+        buffer.clear();
+    }
+    document.flush_emphasis();
+    let lines: Vec<String> = document.source.split('\n').map(Line::escape_comment_head).collect();
+    Ok(lines.join("\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::format::docx;
+    use crate::format::Options;
+    use std::io::Cursor;
+
+    fn document_xml(body: &str) -> String {
+        docx::document(body, &Options::default())
+    }
+
+    #[test]
+    fn parse_document_should_convert_paragraphs_to_lines() {
+        let xml = document_xml(&format!(
+            "<w:p>{}</w:p><w:p/><w:p>{}</w:p>",
+            docx::run("我が輩は猫である。"),
+            docx::run("「名前は？」")
+        ));
+        let source = parse_document(&xml, &HashMap::new()).unwrap();
+        assert_eq!(source, "我が輩は猫である。\n\n「名前は？」\n");
+    }
+
+    #[test]
+    fn parse_document_should_convert_ruby() {
+        let xml = document_xml(&format!(
+            "<w:p>{}{}</w:p>",
+            docx::ruby("猫", "ねこ"),
+            docx::run("である")
+        ));
+        assert_eq!(parse_document(&xml, &HashMap::new()).unwrap(), "[猫:ねこ]である\n");
+    }
+
+    #[test]
+    fn parse_document_should_merge_emphasized_runs_to_kenten() {
+        let xml = document_xml(&format!(
+            "<w:p>{}{}{}</w:p>",
+            docx::emphasis("名前"),
+            docx::emphasis("は"),
+            docx::run("まだない")
+        ));
+        assert_eq!(parse_document(&xml, &HashMap::new()).unwrap(), "[名前は:.]まだない\n");
+    }
+
+    #[test]
+    fn parse_document_should_not_convert_run_without_emphasis_mark() {
+        let xml = document_xml("<w:p><w:r><w:rPr><w:em w:val=\"none\"/></w:rPr><w:t>猫</w:t></w:r></w:p>");
+        assert_eq!(parse_document(&xml, &HashMap::new()).unwrap(), "猫\n");
+    }
+
+    #[test]
+    fn parse_document_should_convert_comment() {
+        let xml = document_xml(&format!(
+            "<w:p>{}<w:r><w:commentReference w:id=\"0\"/></w:r>{}</w:p>",
+            docx::run("猫"),
+            docx::run("である")
+        ));
+        let mut comments = HashMap::new();
+        comments.insert("0".to_string(), "犬にするか要検討".to_string());
+        assert_eq!(
+            parse_document(&xml, &comments).unwrap(),
+            "猫[#犬にするか要検討]である\n"
+        );
+    }
+
+    #[test]
+    fn parse_document_should_unescape_text() {
+        let xml = document_xml(&format!("<w:p>{}</w:p>", docx::run("&lt;猫&gt; &amp; 犬")));
+        assert_eq!(parse_document(&xml, &HashMap::new()).unwrap(), "<猫> & 犬\n");
+    }
+
+    #[test]
+    fn parse_document_should_escape_brackets_and_comment_head() {
+        let xml = document_xml(&format!("<w:p>{}</w:p>", docx::run("// [HP:100]")));
+        assert_eq!(parse_document(&xml, &HashMap::new()).unwrap(), "\\// \\[HP:100\\]\n");
+    }
+
+    #[test]
+    fn parse_comments_should_collect_texts_by_id() {
+        let xml = "<w:comments xmlns:w=\"http://schemas.openxmlformats.org/wordprocessingml/2006/main\">\
+                   <w:comment w:id=\"3\" w:author=\"editor\"><w:p><w:r><w:t>要</w:t></w:r><w:r><w:t>検討</w:t></w:r></w:p></w:comment>\
+                   </w:comments>";
+        let comments = parse_comments(xml).unwrap();
+        assert_eq!(comments.get("3").unwrap(), "要検討");
+    }
+
+    #[test]
+    fn import_should_read_exported_document() {
+        let xml = document_xml(&format!(
+            "{}\n{}",
+            docx::paragraph(&format!("{}{}", docx::ruby("猫", "ねこ"), docx::emphasis("です")), 1),
+            docx::paragraph(&docx::run("「はい」"), 0)
+        ));
+        let mut package = Cursor::new(Vec::new());
+        docx::write(&mut package, &xml, &Options::default()).unwrap();
+        assert_eq!(import(package).unwrap(), "[猫:ねこ][です:.]\n「はい」\n");
+    }
+
+    #[test]
+    fn import_should_return_error_when_not_zip() {
+        assert!(import(Cursor::new(b"plain text".to_vec())).is_err());
+    }
+}
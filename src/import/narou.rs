@@ -0,0 +1,174 @@
+use crate::entities::line::Line;
+use crate::entities::syntax;
+use regex::{Captures, Regex};
+
+/// Import text written in Narou or Kakuyomu notation as naromat source text
+///
+/// * `｜親《ルビ》` and `漢字《かんじ》` become `[親:ルビ]`
+/// * `｜強調《・・》` and `《《強調》》` become `[強調:.]`
+/// * 2 byte whitespace at line head and after exclamations, which naromat inserts, is removed, and the author's indent is kept
+/// * Brackets and `//` at line head are escaped
+///
+/// # Example
+///
+/// ```
+/// use naromat::import::narou::import;
+///
+/// let source = import("　どこで｜生まれた《・・・・》のかとんと見当《けんとう》がつかぬ！　\n 「猫」");
+/// assert_eq!(source, "どこで[生まれた:.]のかとんと[見当:けんとう]がつかぬ！\n「猫」");
+/// ```
+pub fn import(text: &str) -> String {
+    text.split('\n').map(import_line).collect::<Vec<String>>().join("\n")
+}
+
+/// Import a line
+fn import_line(line: &str) -> String {
+    let line = Line::escape_comment_head(&syntax::escape(drop_header_space(line)));
+    let line = drop_space_after_exclamation(&line);
+    let line = convert_kenten(&line);
+    let line = convert_explicit_ruby(&line);
+    convert_implicit_ruby(&line)
+}
+
+/// Drop whitespace inserted to line head
+///
+/// The author's indent is rendered as written, so a 2 byte whitespace is dropped only if it comes back as the header.
+fn drop_header_space(line: &str) -> &str {
+    if let Some(rest) = line.strip_prefix('　') {
+        return match !rest.starts_with('　') && Line::new(rest).get().starts_with('　') {
+            true => rest,
+            false => line,
+        };
+    }
+    match line.strip_prefix(' ') {
+        Some(line) if line.starts_with('「') => line,
+        _ => line,
+    }
+}
+
+/// Drop 2 byte whitespace inserted to after of exclamation
+fn drop_space_after_exclamation(line: &str) -> String {
+    let exclamations = Regex::new(r"(!\?|\?!|[！？])　").unwrap();
+    exclamations.replace_all(line, "$1").to_string()
+}
+
+/// Convert Kakuyomu kenten(`《《強調》》`)
+fn convert_kenten(line: &str) -> String {
+    let kenten = Regex::new(r"《《([^《》]+)》》").unwrap();
+    kenten
+        .replace_all(line, |caps: &Captures| format!("[{}:.]", escape_markup(&caps[1])))
+        .to_string()
+}
+
+/// Convert ruby which starts with `｜`
+pub(crate) fn convert_explicit_ruby(line: &str) -> String {
+    let ruby = Regex::new(r"[｜|]([^｜|《》]+)《([^《》]+)》").unwrap();
+    ruby.replace_all(line, |caps: &Captures| to_source(&caps[1], &caps[2]))
+        .to_string()
+}
+
+/// Convert ruby whose parent is a run of kanji
+pub(crate) fn convert_implicit_ruby(line: &str) -> String {
+    let ruby = Regex::new(r"([\p{Han}々〆ヵヶ]+)《([^《》]+)》").unwrap();
+    ruby.replace_all(line, |caps: &Captures| to_source(&caps[1], &caps[2]))
+        .to_string()
+}
+
+/// Source of ruby, or kenten if the ruby consists of `・`
+fn to_source(parent: &str, ruby: &str) -> String {
+    if ruby.chars().all(|character| character == '・') {
+        return format!("[{}:.]", escape_markup(parent));
+    }
+    format!("[{}:{}]", escape_markup(parent), escape_markup(ruby))
+}
+
+/// Escape `:` and `.` which separate or split ruby, as brackets are escaped with the whole line
+pub(crate) fn escape_markup(text: &str) -> String {
+    text.replace(':', r"\:").replace('.', r"\.")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entities::chapter::Chapter;
+
+    #[test]
+    fn import_should_round_trip_through_chapter() {
+        let source = "我が輩は猫である。名前はまだない！
+どこで[生まれた:.]のかとんと[見当:けんとう]がつかぬ。
+「[吾輩:わがはい]は？」";
+        let narou = Chapter::new(source).get();
+        assert_eq!(import(&narou), source);
+    }
+
+    #[test]
+    fn import_should_escape_markup_in_ruby_and_kenten() {
+        // given
+        let narou = "　｜10:30《じゅうじはん》に｜丸《o》と｜星《*》を｜東京.都《とう.きょう》で《《v1.0》》を見た。";
+
+        // when
+        let source = import(narou);
+
+        // then
+        assert_eq!(
+            source,
+            r"[10\:30:じゅうじはん]に[丸:o]と[星:*]を[東京\.都:とう\.きょう]で[v1\.0:.]を見た。"
+        );
+        assert_eq!(
+            Chapter::new(&source).get(),
+            narou.replace("《《v1.0》》", "｜v1.0《・・・・》")
+        );
+    }
+
+    #[test]
+    fn import_should_round_trip_author_indent_through_chapter() {
+        let narou = "　　猫が鳴いた。\n　「猫」\n「犬」";
+        assert_eq!(Chapter::new(&import(narou)).get(), narou);
+    }
+
+    #[test]
+    fn import_line_should_escape_brackets_and_comment_head() {
+        assert_eq!(import_line("　[HP:100]"), r"\[HP:100\]");
+        assert_eq!(import_line("　//｜猫《ねこ》"), r"\//[猫:ねこ]");
+    }
+
+    #[test]
+    fn drop_header_space_should_keep_author_indent() {
+        assert_eq!(drop_header_space("　猫"), "猫");
+        assert_eq!(drop_header_space("　　猫"), "　　猫");
+        assert_eq!(drop_header_space("　「猫」"), "　「猫」");
+        assert_eq!(drop_header_space(" 「猫」"), "「猫」");
+        assert_eq!(drop_header_space(" 猫"), " 猫");
+    }
+
+    #[test]
+    fn convert_kenten_should_convert_kakuyomu_kenten() {
+        assert_eq!(convert_kenten("私の《《名前》》は"), "私の[名前:.]は");
+    }
+
+    #[test]
+    fn convert_explicit_ruby_should_convert_half_and_full_width_bar() {
+        assert_eq!(
+            convert_explicit_ruby("｜名前《なまえ》と|太郎《たろう》"),
+            "[名前:なまえ]と[太郎:たろう]"
+        );
+    }
+
+    #[test]
+    fn convert_implicit_ruby_should_take_kanji_run_as_parent() {
+        assert_eq!(
+            convert_implicit_ruby("これは人々《ひとびと》の見当《けんとう》"),
+            "これは[人々:ひとびと]の[見当:けんとう]"
+        );
+    }
+
+    #[test]
+    fn convert_implicit_ruby_should_not_convert_after_kana() {
+        assert_eq!(convert_implicit_ruby("これは《注釈》"), "これは《注釈》");
+    }
+
+    #[test]
+    fn import_line_should_keep_escaped_bracket() {
+        assert_eq!(import_line("　漢字｜《かんじ》"), "漢字｜《かんじ》");
+    }
+}
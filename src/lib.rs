@@ -1,2 +1,5 @@
+pub mod config;
 pub mod entities;
 pub mod errors;
+pub mod format;
+pub mod import;
@@ -1,13 +1,80 @@
 #[macro_use]
 extern crate clap;
 
-use clap::Arg;
+use clap::{AppSettings, Arg, ArgMatches, SubCommand};
+use naromat::config::Config;
+use naromat::entities::book::Book;
+use naromat::entities::glossary::{Glossary, GlossaryMode};
+use naromat::entities::line::{IndentRules, SourceRules};
+use naromat::entities::punctuation::PunctuationRules;
+use naromat::entities::ruby_index::RubyIndex;
+use naromat::entities::scene_break::SceneBreakStyle;
+use naromat::entities::syntax::KentenStyle;
 use naromat::entities::text_file::TextFile;
+use naromat::entities::width::WidthRules;
 use naromat::errors::*;
-use std::path::Path;
+use naromat::format::{BlankLinePolicy, Format, Options};
+use naromat::import::{self, Dialect};
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
 
 fn main() {
     let app = app_from_crate!()
+        .setting(AppSettings::SubcommandsNegateReqs)
+        .subcommand(
+            SubCommand::with_name("import")
+                .about("import Word document(.docx) or Narou/Kakuyomu text as naromat source")
+                .arg(Arg::with_name("source").help("source file/dir path").required(true))
+                .arg(
+                    Arg::with_name("dest")
+                        .help("file/dir path to save(default: source path with txt or source.txt extension)")
+                        .short("d")
+                        .long("dest")
+                        .takes_value(true),
+                )
+                .arg(
+                    Arg::with_name("ext")
+                        .help("ext to process in dir(default: docx or txt)")
+                        .short("e")
+                        .long("ext")
+                        .takes_value(true),
+                )
+                .arg(
+                    Arg::with_name("from")
+                        .help("notation of source(default: docx if source ext is docx, otherwise narou)")
+                        .short("f")
+                        .long("from")
+                        .possible_values(&["docx", "narou", "kakuyomu"])
+                        .takes_value(true),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("glossary")
+                .about("collect ruby of source files with occurrences and flag terms which have different readings")
+                .arg(Arg::with_name("source").help("source file/dir path").required(true))
+                .arg(
+                    Arg::with_name("dest")
+                        .help("file path to save(default: standard output)")
+                        .short("d")
+                        .long("dest")
+                        .takes_value(true),
+                )
+                .arg(
+                    Arg::with_name("ext")
+                        .help("ext to process in dir(default: txt or [input] ext of naromat.toml)")
+                        .short("e")
+                        .long("ext")
+                        .takes_value(true),
+                )
+                .arg(
+                    Arg::with_name("format")
+                        .help("format to write(default: json if dest ext is json, otherwise csv)")
+                        .short("f")
+                        .long("format")
+                        .possible_values(&["csv", "json"])
+                        .takes_value(true),
+                ),
+        )
         .arg(Arg::with_name("source").help("source text file path").required(true))
         .arg(
             Arg::with_name("dest")
@@ -18,43 +85,604 @@ fn main() {
         )
         .arg(
             Arg::with_name("ext")
-                .help("ext to process(default: txt)")
+                .help("ext to process(default: txt or [input] ext of naromat.toml)")
                 .short("e")
                 .long("ext")
                 .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("output-ext")
+                .help("ext of saved files in dir(default: ext of target format or source)")
+                .long("output-ext")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("target")
+                .help("format to convert to")
+                .short("t")
+                .long("target")
+                .possible_values(&["narou", "kakuyomu", "aozora", "pixiv", "html", "epub", "latex", "docx"])
+                .default_value("narou"),
+        )
+        .arg(
+            Arg::with_name("dialect")
+                .help("notation which source is written in")
+                .long("dialect")
+                .possible_values(&["naromat", "aozora"])
+                .default_value("naromat"),
+        )
+        .arg(
+            Arg::with_name("glossary")
+                .help("glossary file of terms and readings to add ruby(tsv or toml)")
+                .long("glossary")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("glossary-mode")
+                .help("occurrences of glossary terms to add ruby")
+                .long("glossary-mode")
+                .possible_values(&["every", "first"])
+                .default_value("every"),
+        )
+        .arg(
+            Arg::with_name("kenten")
+                .help("style of kenten written as [text:.]")
+                .long("kenten")
+                .possible_values(&["dot", "sesame", "open-circle", "filled-circle"])
+                .default_value("dot"),
+        )
+        .arg(
+            Arg::with_name("punctuation")
+                .help("comma separated rules to normalize punctuation, or none to keep punctuation in source")
+                .long("punctuation")
+                .takes_value(true)
+                .use_delimiter(true)
+                .possible_values(&[
+                    "all",
+                    "none",
+                    "ellipsis",
+                    "dash",
+                    "even",
+                    "exclamation",
+                    "double-exclamation",
+                    "period",
+                ]),
+        )
+        .arg(
+            Arg::with_name("normalize-width")
+                .help("convert digits and half width katakana to the width for vertical readers, and mark two digits for tate-chu-yoko(html/epub only), with [width] rules of naromat.toml if any")
+                .long("normalize-width"),
+        )
+        .arg(
+            Arg::with_name("no-normalize-width")
+                .help("keep width in source even if naromat.toml normalizes it")
+                .long("no-normalize-width")
+                .conflicts_with("normalize-width"),
+        )
+        .arg(
+            Arg::with_name("full-width-latin")
+                .help("also convert latin letters to full width(with --normalize-width)")
+                .long("full-width-latin"),
+        )
+        .arg(
+            Arg::with_name("no-full-width-latin")
+                .help("keep latin letters half width even if naromat.toml converts them")
+                .long("no-full-width-latin")
+                .conflicts_with("full-width-latin"),
+        )
+        .arg(
+            Arg::with_name("no-tate-chu-yoko")
+                .help("keep two digits half width without tate-chu-yoko(with --normalize-width)")
+                .long("no-tate-chu-yoko"),
+        )
+        .arg(
+            Arg::with_name("tate-chu-yoko")
+                .help("mark two digits for tate-chu-yoko even if naromat.toml disables it(html/epub only)")
+                .long("tate-chu-yoko")
+                .conflicts_with("no-tate-chu-yoko"),
+        )
+        .arg(
+            Arg::with_name("exclamation-space")
+                .help("whitespace inserted after exclamation(！, ？)")
+                .long("exclamation-space")
+                .default_value("　"),
+        )
+        .arg(
+            Arg::with_name("indent")
+                .help("indent of paragraphs")
+                .long("indent")
+                .default_value("　"),
+        )
+        .arg(
+            Arg::with_name("indent-rules")
+                .help("comma separated line heads and their indents as HEAD=INDENT, HEAD only for no indent(default: 「,『,（)")
+                .long("indent-rules")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("ignore-source-indent")
+                .help("apply indent rules also to lines which the author indented with 2 byte whitespaces")
+                .long("ignore-source-indent"),
+        )
+        .arg(
+            Arg::with_name("preserve-source-indent")
+                .help("keep indent of the author even if naromat.toml ignores it")
+                .long("preserve-source-indent")
+                .conflicts_with("ignore-source-indent"),
+        )
+        .arg(
+            Arg::with_name("scene-break-markers")
+                .help("comma separated lines which are scene breaks(default: †,＊,＊＊＊,◇,◆,◇◆◇,◆◇◆)")
+                .long("scene-break-markers")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("scene-break-marker")
+                .help("marker to render every scene break with(default: marker in source)")
+                .long("scene-break-marker")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("scene-break-blank-run")
+                .help("number of consecutive empty lines which is a scene break")
+                .long("scene-break-blank-run")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("scene-break-padding")
+                .help("number of 2 byte whitespaces before a scene break marker")
+                .long("scene-break-padding")
+                .default_value("0"),
+        )
+        .arg(
+            Arg::with_name("scene-break-margin")
+                .help("number of empty lines before and after a scene break")
+                .long("scene-break-margin")
+                .default_value("0"),
+        )
+        .arg(
+            Arg::with_name("aozora-indented-break")
+                .help("render scene breaks as indented markers instead of page breaks in aozora")
+                .long("aozora-indented-break"),
+        )
+        .arg(
+            Arg::with_name("aozora-page-break")
+                .help("render scene breaks as page breaks in aozora even if naromat.toml indents them")
+                .long("aozora-page-break")
+                .conflicts_with("aozora-indented-break"),
+        )
+        .arg(
+            Arg::with_name("strict")
+                .help("fail without saving when source has markup errors or violates ruby limits of target")
+                .long("strict"),
+        )
+        .arg(
+            Arg::with_name("no-strict")
+                .help("save even if naromat.toml enables strict mode")
+                .long("no-strict")
+                .conflicts_with("strict"),
+        )
+        .arg(
+            Arg::with_name("vertical")
+                .help("write vertically(html/epub/latex/docx only)")
+                .long("vertical"),
+        )
+        .arg(
+            Arg::with_name("no-vertical")
+                .help("write horizontally even if naromat.toml writes vertically")
+                .long("no-vertical")
+                .conflicts_with("vertical"),
+        )
+        .arg(
+            Arg::with_name("title")
+                .help("title of the book(html/epub/latex/docx only, default: source dir name)")
+                .long("title")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("author")
+                .help("author of the book(epub/latex/docx only)")
+                .long("author")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("combine-sections")
+                .help("save preface(@preface) and afterword(@afterword) to the same file as body")
+                .long("combine-sections"),
+        )
+        .arg(
+            Arg::with_name("no-combine-sections")
+                .help("save preface and afterword to separate files even if naromat.toml combines them")
+                .long("no-combine-sections")
+                .conflicts_with("combine-sections"),
+        )
+        .arg(
+            Arg::with_name("blank-lines")
+                .help("policy of empty lines between paragraphs")
+                .long("blank-lines")
+                .possible_values(&["preserve", "paragraph", "transition", "collapse"])
+                .default_value("preserve"),
+        )
+        .arg(
+            Arg::with_name("max-blank-lines")
+                .help("max number of consecutive empty lines(collapse policy only)")
+                .long("max-blank-lines")
+                .default_value("1"),
+        )
+        .arg(
+            Arg::with_name("preamble")
+                .help("wrap with document preamble(latex only)")
+                .long("preamble"),
+        )
+        .arg(
+            Arg::with_name("no-preamble")
+                .help("write without document preamble even if naromat.toml wraps with it")
+                .long("no-preamble")
+                .conflicts_with("preamble"),
         );
     let matches = app.get_matches();
+    if let Some(matches) = matches.subcommand_matches("import") {
+        let source = Path::new(matches.value_of("source").unwrap());
+        let from = match matches.value_of("from") {
+            Some(from) => from,
+            None if source.extension().is_some_and(|extension| extension == "docx") => "docx",
+            None => "narou",
+        };
+        let result = if source.is_dir() {
+            let dest = matches.value_of("dest").unwrap_or("./");
+            let default_ext = if from == "docx" { "docx" } else { "txt" };
+            let ext = matches.value_of("ext").unwrap_or(default_ext);
+            import_dir(source, dest, ext, from)
+        } else {
+            let dest = match matches.value_of("dest") {
+                Some(dest) => PathBuf::from(dest),
+                None => import_path(source, from),
+            };
+            import_file(source, &dest, from)
+        };
+        exit(result.is_ok());
+    }
+    if let Some(matches) = matches.subcommand_matches("glossary") {
+        let source = Path::new(matches.value_of("source").unwrap());
+        let dest = matches.value_of("dest");
+        let format = match matches.value_of("format") {
+            Some(format) => format,
+            None if dest.is_some_and(|dest| dest.ends_with(".json")) => "json",
+            None => "csv",
+        };
+        let config = match load_config(source) {
+            Ok(config) => config,
+            Err(err) => {
+                println!("config cannot be loaded: {}", err);
+                return exit(false);
+            }
+        };
+        let ext = matches.value_of("ext").unwrap_or(&config.ext);
+        return exit(extract_glossary(source, dest, ext, format, config.dialect, &config.source_rules).is_ok());
+    }
     let source = matches.value_of("source").unwrap_or("./");
     let source = Path::new(source);
     let dest = matches.value_of("dest").unwrap_or("./");
-    let ext = matches.value_of("ext").unwrap_or("txt");
-    if source.is_file() {
-        match process_file(source, dest, ext) {
-            Ok(_) => 0,
-            Err(_) => 1,
+    let config = match load_config(source) {
+        Ok(config) => config,
+        Err(err) => {
+            println!("config cannot be loaded: {}", err);
+            return exit(false);
+        }
+    };
+    let format = match explicit(&matches, "target") {
+        Some(_) => value_t!(matches, "target", Format).unwrap_or_else(|e| e.exit()),
+        None => config.target,
+    };
+    let glossary_paths = match matches.value_of("glossary") {
+        Some(path) => vec![PathBuf::from(path)],
+        None => config.glossaries.clone(),
+    };
+    let glossary = match load_glossaries(&glossary_paths) {
+        Ok(glossary) => glossary,
+        Err(err) => {
+            println!("glossary cannot be loaded: {}", err);
+            return exit(false);
+        }
+    };
+    let scene_breaks = SceneBreakStyle {
+        markers: match matches.value_of("scene-break-markers") {
+            Some(markers) => markers.split(',').map(String::from).collect(),
+            None => config.scene_breaks.markers.clone(),
+        },
+        blank_run: if matches.is_present("scene-break-blank-run") {
+            Some(value_t!(matches, "scene-break-blank-run", usize).unwrap_or_else(|e| e.exit()))
+        } else {
+            config.scene_breaks.blank_run
+        },
+        marker: match matches.value_of("scene-break-marker") {
+            Some(marker) => Some(marker.to_string()),
+            None => config.scene_breaks.marker.clone(),
+        },
+        padding: match explicit(&matches, "scene-break-padding") {
+            Some(_) => value_t!(matches, "scene-break-padding", usize).unwrap_or_else(|e| e.exit()),
+            None => config.scene_breaks.padding,
+        },
+        margin: match explicit(&matches, "scene-break-margin") {
+            Some(_) => value_t!(matches, "scene-break-margin", usize).unwrap_or_else(|e| e.exit()),
+            None => config.scene_breaks.margin,
+        },
+        aozora_page_break: switch(
+            &matches,
+            "aozora-page-break",
+            "aozora-indented-break",
+            config.scene_breaks.aozora_page_break,
+        ),
+    };
+    let width = if matches.is_present("normalize-width") {
+        Some(config.width_rules_for(format))
+    } else if matches.is_present("no-normalize-width") {
+        None
+    } else {
+        config.width_for(format)
+    };
+    let input = Input {
+        ext: matches.value_of("ext").unwrap_or(&config.ext),
+        dialect: match explicit(&matches, "dialect") {
+            Some("aozora") => Dialect::Aozora,
+            Some(_) => Dialect::Naromat,
+            None => config.dialect,
+        },
+        source_rules: config.source_rules.clone(),
+        strict: switch(&matches, "strict", "no-strict", config.strict),
+        glossary,
+        glossary_mode: match explicit(&matches, "glossary-mode") {
+            Some("first") => GlossaryMode::First,
+            Some(_) => GlossaryMode::Every,
+            None => config.glossary_mode,
+        },
+        scene_breaks,
+        kenten: match explicit(&matches, "kenten") {
+            Some("sesame") => KentenStyle::Sesame,
+            Some("open-circle") => KentenStyle::OpenCircle,
+            Some("filled-circle") => KentenStyle::FilledCircle,
+            Some(_) => KentenStyle::Dot,
+            None => config.kenten,
+        },
+        punctuation: match matches.values_of("punctuation") {
+            Some(rules) => Some(punctuation_rules(rules.collect())),
+            None => config.punctuation,
+        },
+        width: width.map(|rules| WidthRules {
+            tate_chu_yoko: switch(&matches, "tate-chu-yoko", "no-tate-chu-yoko", rules.tate_chu_yoko),
+            latin: switch(&matches, "full-width-latin", "no-full-width-latin", rules.latin),
+            ..rules
+        }),
+        indent: IndentRules {
+            indent: match explicit(&matches, "indent") {
+                Some(indent) => indent.to_string(),
+                None => config.indent.indent.clone(),
+            },
+            heads: match matches.value_of("indent-rules") {
+                Some(rules) => rules.split(',').map(indent_rule).collect(),
+                None => config.indent.heads.clone(),
+            },
+            preserve: switch(
+                &matches,
+                "preserve-source-indent",
+                "ignore-source-indent",
+                config.indent.preserve,
+            ),
+        },
+        exclamation_space: match explicit(&matches, "exclamation-space") {
+            Some(space) => space.to_string(),
+            None => config.exclamation_space.clone(),
+        },
+    };
+    let max_blank_lines = match (explicit(&matches, "max-blank-lines"), config.options.blank_lines) {
+        (None, BlankLinePolicy::Collapse(max)) => max,
+        _ => value_t!(matches, "max-blank-lines", usize).unwrap_or_else(|e| e.exit()),
+    };
+    let options = Options {
+        title: matches.value_of("title").map(String::from).or(config.options.title),
+        author: matches.value_of("author").map(String::from).or(config.options.author),
+        vertical: switch(&matches, "vertical", "no-vertical", config.options.vertical),
+        preamble: switch(&matches, "preamble", "no-preamble", config.options.preamble),
+        combine_sections: switch(
+            &matches,
+            "combine-sections",
+            "no-combine-sections",
+            config.options.combine_sections,
+        ),
+        blank_lines: match explicit(&matches, "blank-lines") {
+            Some("paragraph") => BlankLinePolicy::EveryParagraph,
+            Some("transition") => BlankLinePolicy::Transition,
+            Some("collapse") => BlankLinePolicy::Collapse(max_blank_lines),
+            Some(_) => BlankLinePolicy::Preserve,
+            None => match config.options.blank_lines {
+                BlankLinePolicy::Collapse(_) => BlankLinePolicy::Collapse(max_blank_lines),
+                policy => policy,
+            },
+        },
+    };
+    let output_ext = matches.value_of("output-ext").or(config.output_ext.as_deref());
+    if source.is_dir() && format == Format::Epub {
+        let title = match options.title {
+            Some(ref title) => title.clone(),
+            None => source_name(source),
+        };
+        let dest = match matches.value_of("dest") {
+            Some(dest) => dest.to_string(),
+            None => format!("./{}.epub", title),
+        };
+        let options = Options {
+            title: Some(title),
+            ..options
         };
+        exit(pack_dir(source, &dest, &input, &options).is_ok());
+    } else if source.is_file() {
+        exit(process_file(source, dest, &input, format, &options).is_ok());
     } else {
-        match process_dir(source, dest, ext) {
-            Ok(_) => 0,
-            Err(_) => 1,
+        exit(process_dir(source, dest, &input, format, output_ext, &options).is_ok());
+    }
+}
+
+/// Settings of how source files are read
+struct Input<'a> {
+    /// Extension of source files to process
+    ext: &'a str,
+    dialect: Dialect,
+    /// Rules of how comments and sentences in source are read
+    source_rules: SourceRules,
+    /// Fail without saving when source has markup errors
+    strict: bool,
+    glossary: Option<Glossary>,
+    glossary_mode: GlossaryMode,
+    scene_breaks: SceneBreakStyle,
+    /// Style of kenten which has no explicit style
+    kenten: KentenStyle,
+    /// Rules to normalize punctuation, or `None` to keep punctuation in source
+    punctuation: Option<PunctuationRules>,
+    /// Rules to normalize width of characters, or `None` to keep width in source
+    width: Option<WidthRules>,
+    indent: IndentRules,
+    /// Whitespace inserted after exclamation
+    exclamation_space: String,
+}
+
+impl<'a> Input<'a> {
+    /// Settings to read naromat source files which have designated extension
+    #[cfg(test)]
+    fn new(ext: &'a str) -> Self {
+        Self {
+            ext,
+            dialect: Dialect::Naromat,
+            source_rules: SourceRules::default(),
+            strict: false,
+            glossary: None,
+            glossary_mode: GlossaryMode::Every,
+            scene_breaks: SceneBreakStyle::default(),
+            kenten: KentenStyle::Dot,
+            punctuation: None,
+            width: None,
+            indent: IndentRules::default(),
+            exclamation_space: "　".to_string(),
+        }
+    }
+
+    /// Load a source file
+    fn load(&self, path: &Path) -> Result<TextFile, Box<dyn std::error::Error>> {
+        let path = stringify_path(path).map_err(|err| err.to_string())?;
+        let file = TextFile::new_with(path, self.dialect, &self.source_rules)?
+            .with_scene_breaks(self.scene_breaks.clone())
+            .with_kenten_style(self.kenten)
+            .with_indent_rules(&self.indent)
+            .with_exclamation_space(&self.exclamation_space);
+        // glossary terms are written as in source, so they are looked up before normalization
+        let file = match self.glossary {
+            Some(ref glossary) => file.with_glossary(glossary, self.glossary_mode),
+            None => file,
+        };
+        let file = match self.punctuation {
+            Some(ref rules) => file.with_punctuation(rules),
+            None => file,
         };
+        Ok(match self.width {
+            Some(ref rules) => file.with_width(rules),
+            None => file,
+        })
+    }
+}
+
+/// Value of an argument which is given in command line, which overrides the config
+fn explicit<'a>(matches: &'a ArgMatches, name: &str) -> Option<&'a str> {
+    if matches.occurrences_of(name) > 0 {
+        matches.value_of(name)
+    } else {
+        None
+    }
+}
+
+/// Value of a flag which is turned on or off in command line, which overrides the config
+fn switch(matches: &ArgMatches, on: &str, off: &str, config: bool) -> bool {
+    if matches.is_present(on) {
+        true
+    } else if matches.is_present(off) {
+        false
+    } else {
+        config
+    }
+}
+
+/// Load config(naromat.toml) in the directory of source or its ancestors, or default config if not found
+fn load_config(source: &Path) -> Result<Config, ConfigError> {
+    match Config::find(source) {
+        Some(path) => {
+            eprintln!("{} : configured", path.display());
+            Config::load(&path)
+        }
+        None => Ok(Config::default()),
+    }
+}
+
+/// Load glossary files merged in order, or `None` if no file is designated
+fn load_glossaries(paths: &[PathBuf]) -> Result<Option<Glossary>, Box<dyn std::error::Error>> {
+    let mut merged: Option<Glossary> = None;
+    for path in paths {
+        let glossary = Glossary::load(stringify_path(path).map_err(|err| err.to_string())?)?;
+        match merged {
+            Some(ref mut merged) => merged.merge(glossary),
+            None => merged = Some(glossary),
+        }
     }
+    Ok(merged)
+}
+
+/// Punctuation rules which are enabled by names(`all` for every rule)
+fn punctuation_rules(names: Vec<&str>) -> PunctuationRules {
+    if names.contains(&"all") {
+        return PunctuationRules::default();
+    }
+    PunctuationRules {
+        ellipsis: names.contains(&"ellipsis"),
+        dash: names.contains(&"dash"),
+        even_marks: names.contains(&"even"),
+        exclamation: names.contains(&"exclamation"),
+        double_exclamation: names.contains(&"double-exclamation"),
+        period_before_bracket: names.contains(&"period"),
+    }
+}
+
+/// Pair of line head and its indent written as `HEAD=INDENT`, or `HEAD` for no indent
+fn indent_rule(rule: &str) -> (String, String) {
+    match rule.find('=') {
+        Some(index) => (rule[..index].to_string(), rule[index + 1..].to_string()),
+        None => (rule.to_string(), String::new()),
+    }
+}
+
+/// Exit with status code which represents succeed or not
+fn exit(is_succeed: bool) {
+    std::process::exit(if is_succeed { 0 } else { 1 })
 }
 
 fn process_file<'file_process>(
     source: &Path,
     target: &'file_process str,
-    ext: &str,
+    input: &Input,
+    format: Format,
+    options: &Options,
 ) -> Result<(), FileProcessError<'file_process>> {
-    if source.extension().unwrap() != ext {
-        println!("{} : extension is not a target({})", source.display(), ext);
+    if source.extension().unwrap() != input.ext {
+        println!("{} : extension is not a target({})", source.display(), input.ext);
         return Ok(());
     }
     print!("{} : processing", source.display());
-    match TextFile::new(stringify_path(source).unwrap()) {
+    match input.load(source) {
         Ok(file) => {
             print!("...loaded");
-            match file.format_and_save(target) {
+            let diagnostics = file.diagnostics_for(format);
+            report_diagnostics(source, &diagnostics);
+            if input.strict && !diagnostics.is_empty() {
+                println!("..cannot be saved: {} markup errors", diagnostics.len());
+                return Err(FileProcessError::InvalidMarkup(diagnostics));
+            }
+            match file.format_and_save_with(target, format, options) {
                 Ok(_) => {
                     println!("..successfuly saved to {}", target);
                     Ok(())
@@ -72,37 +700,272 @@ fn process_file<'file_process>(
     }
 }
 
-fn stringify_path(path: &Path) -> Result<&str, InvalidPathError> {
+/// Report markup errors of a source file to stderr
+fn report_diagnostics(source: &Path, diagnostics: &[Diagnostic]) {
+    for diagnostic in diagnostics {
+        eprintln!("{}:{}", source.display(), diagnostic);
+    }
+}
+
+fn stringify_path(path: &Path) -> Result<&str, InvalidPathError<'_>> {
     match path.to_str() {
         Some(string) => Ok(string),
         None => Err(InvalidPathError::FileNotFound(path)),
     }
 }
 
-fn process_dir(source: &Path, target: &str, ext: &str) -> Result<(), Box<dyn std::error::Error + 'static>> {
+fn process_dir(
+    source: &Path,
+    target: &str,
+    input: &Input,
+    format: Format,
+    output_ext: Option<&str>,
+    options: &Options,
+) -> Result<(), Box<dyn std::error::Error + 'static>> {
     println!("{} : processing", source.display());
+    let mut failures = 0;
     for entry in source.read_dir()? {
         let entry = entry?;
         let path = entry.path();
         if path.is_dir() {
-            process_dir(&path, target, ext)?
+            process_dir(&path, target, input, format, output_ext, options)?
         } else {
             let parent_dir = path.parent().unwrap().to_str().unwrap();
-            let file_name = path.file_name().unwrap().to_str().expect("File name cannot be parsed");
+            let file_name = match output_ext.or_else(|| format.extension()) {
+                Some(extension) => path.with_extension(extension),
+                None => path.clone(),
+            };
+            let file_name = file_name
+                .file_name()
+                .unwrap()
+                .to_str()
+                .expect("File name cannot be parsed");
             let target_dir = format!("{}/{}", target, parent_dir);
             std::fs::create_dir_all(&target_dir)?;
             let file_name = format!("{}/{}", target_dir, file_name);
-            process_file(&path, file_name.as_str(), ext).unwrap();
+            if process_file(&path, file_name.as_str(), input, format, options).is_err() {
+                failures += 1;
+            }
         }
     }
+    if failures > 0 {
+        return Err(format!("{} files in {} cannot be processed", failures, source.display()).into());
+    }
     Ok(())
 }
 
+fn pack_dir(source: &Path, target: &str, input: &Input, options: &Options) -> Result<(), Box<dyn std::error::Error>> {
+    println!("{} : packing", source.display());
+    let mut book = Book::new();
+    let mut errors = 0;
+    for path in collect_files(source, input.ext)? {
+        print!("{} : processing", path.display());
+        let file = match input.load(&path) {
+            Ok(file) => file,
+            Err(err) => {
+                println!("cannot be loaded: {}", err);
+                return Err(err);
+            }
+        };
+        let diagnostics = file.diagnostics_for(Format::Epub);
+        report_diagnostics(&path, &diagnostics);
+        errors += diagnostics.len();
+        book.push(&source_name(&path), file.into_chapter());
+        println!("...loaded");
+    }
+    if input.strict && errors > 0 {
+        println!("{} : cannot be saved: {} markup errors", source.display(), errors);
+        return Err(format!("{} markup errors in {}", errors, source.display()).into());
+    }
+    match book.save_as_epub(target, options) {
+        Ok(_) => {
+            println!("{} : successfuly saved to {}", source.display(), target);
+            Ok(())
+        }
+        Err(err) => {
+            println!("{} : cannot be saved: {}", source.display(), err);
+            Err(err.to_string().into())
+        }
+    }
+}
+
+fn import_file(source: &Path, target: &Path, from: &str) -> Result<(), Box<dyn std::error::Error>> {
+    print!("{} : importing", source.display());
+    let text = match read_source(source, from) {
+        Ok(text) => text,
+        Err(err) => {
+            println!("...cannot be imported: {}", err);
+            return Err(err);
+        }
+    };
+    print!("...imported");
+    if target.exists() {
+        println!("..cannot be saved: File {} is already exists", target.display());
+        return Err(format!("File {} is already exists", target.display()).into());
+    }
+    fs::write(target, text)?;
+    println!("..successfuly saved to {}", target.display());
+    Ok(())
+}
+
+fn import_dir(source: &Path, target: &str, ext: &str, from: &str) -> Result<(), Box<dyn std::error::Error>> {
+    println!("{} : importing", source.display());
+    for path in collect_files(source, ext)? {
+        if is_imported(&path) {
+            continue;
+        }
+        let relative = path.strip_prefix(source)?;
+        let target_path = Path::new(target).join(import_path(relative, from));
+        std::fs::create_dir_all(target_path.parent().unwrap())?;
+        import_file(&path, &target_path, from)?;
+    }
+    Ok(())
+}
+
+/// Return true if a file is naromat source which is imported before(`*.source.txt`)
+fn is_imported(path: &Path) -> bool {
+    path.file_name()
+        .is_some_and(|name| name.to_string_lossy().ends_with(".source.txt"))
+}
+
+/// Collect ruby of source files and write them as CSV or JSON
+fn extract_glossary(
+    source: &Path,
+    target: Option<&str>,
+    ext: &str,
+    format: &str,
+    dialect: Dialect,
+    rules: &SourceRules,
+) -> Result<RubyIndex, Box<dyn std::error::Error>> {
+    let files = if source.is_dir() {
+        collect_files(source, ext)?
+    } else {
+        vec![source.to_path_buf()]
+    };
+    let mut index = RubyIndex::new();
+    for path in files {
+        let text = fs::read_to_string(&path)?;
+        index.collect_with(
+            stringify_path(&path).map_err(|err| err.to_string())?,
+            &text,
+            dialect,
+            rules,
+        );
+    }
+    for parent in index.conflicts() {
+        eprintln!("{} : read as {}", parent, index.readings(parent).join(", "));
+    }
+    let output = match format {
+        "json" => index.to_json(),
+        _ => index.to_csv(),
+    };
+    match target {
+        Some(target) => {
+            fs::write(target, output)?;
+            println!("{} : successfuly saved to {}", source.display(), target);
+        }
+        None => print!("{}", output),
+    }
+    Ok(index)
+}
+
+/// Read source file written in designated notation as naromat source
+fn read_source(source: &Path, from: &str) -> Result<String, Box<dyn std::error::Error>> {
+    match from {
+        "docx" => Ok(import::docx::import(File::open(source)?)?),
+        _ => Ok(import::narou::import(&fs::read_to_string(source)?)),
+    }
+}
+
+/// Path to save imported source
+fn import_path(source: &Path, from: &str) -> PathBuf {
+    match from {
+        "docx" => source.with_extension("txt"),
+        _ => source.with_extension("source.txt"),
+    }
+}
+
+/// Collect files which have designated extension in the natural order of names(`2.txt` before `10.txt`)
+fn collect_files(source: &Path, ext: &str) -> Result<Vec<PathBuf>, Box<dyn std::error::Error>> {
+    let mut entries: Vec<PathBuf> = source
+        .read_dir()?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<Result<_, _>>()?;
+    entries.sort_by_cached_key(|path| (natural_key(&source_name(path)), path.clone()));
+    let mut files = Vec::new();
+    for path in entries {
+        if path.is_dir() {
+            files.append(&mut collect_files(&path, ext)?);
+        } else if path.extension().is_some_and(|extension| extension == ext) {
+            files.push(path);
+        }
+    }
+    Ok(files)
+}
+
+/// Key to sort names in natural order, which compares runs of digits as numbers
+fn natural_key(name: &str) -> Vec<(u8, usize, String)> {
+    let mut key = Vec::new();
+    let mut characters = name.chars().peekable();
+    while let Some(character) = characters.next() {
+        let is_digit = character.is_ascii_digit();
+        let mut run = character.to_string();
+        while let Some(next) = characters.next_if(|next| next.is_ascii_digit() == is_digit) {
+            run.push(next);
+        }
+        if is_digit {
+            let number = run.trim_start_matches('0').to_string();
+            key.push((0, number.len(), number));
+        } else {
+            key.push((1, 0, run));
+        }
+    }
+    key
+}
+
+fn source_name(path: &Path) -> String {
+    path.file_stem()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use std::fs::*;
 
+    #[test]
+    fn can_load_glossaries_of_config() {
+        // given
+        let source = Path::new("./resources/test/config/project/episodes/1.txt");
+
+        // when
+        let config = load_config(source).unwrap();
+        let glossary = load_glossaries(&config.glossaries).unwrap();
+
+        // assert
+        assert_eq!(config.target, Format::Kakuyomu);
+        assert_eq!(glossary.unwrap().len(), 1);
+    }
+
+    #[test]
+    fn switch_should_override_config_in_both_ways() {
+        // given
+        let app = clap::App::new("naromat")
+            .arg(Arg::with_name("strict").long("strict"))
+            .arg(Arg::with_name("no-strict").long("no-strict"));
+
+        // when
+        let on = app.clone().get_matches_from(vec!["naromat", "--strict"]);
+        let off = app.clone().get_matches_from(vec!["naromat", "--no-strict"]);
+        let none = app.get_matches_from(vec!["naromat"]);
+
+        // assert
+        assert!(switch(&on, "strict", "no-strict", false));
+        assert!(!switch(&off, "strict", "no-strict", true));
+        assert!(switch(&none, "strict", "no-strict", true));
+    }
+
     #[test]
     fn can_process_recursively() {
         // given
@@ -114,7 +977,15 @@ mod tests {
         let source_file_count = source_dir_path.ancestors().count();
 
         // when
-        process_dir(source_dir_path, target_dir, target_ext).unwrap();
+        process_dir(
+            source_dir_path,
+            target_dir,
+            &Input::new(target_ext),
+            Format::Narou,
+            None,
+            &Options::default(),
+        )
+        .unwrap();
 
         // then
         let target_file_count = target_dir_path.ancestors().count();
@@ -136,7 +1007,14 @@ mod tests {
         let source_file_path = Path::new(source_file);
 
         // when
-        process_file(source_file_path, target_file, target_ext).unwrap();
+        process_file(
+            source_file_path,
+            target_file,
+            &Input::new(target_ext),
+            Format::Narou,
+            &Options::default(),
+        )
+        .unwrap();
 
         // then
         let is_processed_file_exists = target_file_path.exists();
@@ -158,7 +1036,14 @@ mod tests {
         let source_file_path = Path::new(source_file);
 
         // when
-        process_file(source_file_path, target_file, target_ext).unwrap();
+        process_file(
+            source_file_path,
+            target_file,
+            &Input::new(target_ext),
+            Format::Narou,
+            &Options::default(),
+        )
+        .unwrap();
 
         // then
         let is_processed_file_is_not_exists = !target_file_path.exists();
@@ -166,4 +1051,322 @@ mod tests {
         // assert
         assert!(is_processed_file_is_not_exists)
     }
+
+    #[test]
+    fn can_pack_recursively() {
+        // given
+        let target_ext = "txt";
+        let source_dir = "./resources/test/main/can_process_recursively";
+        let target_file = "./resources/test/main/can_pack_recursively.epub";
+        let source_dir_path = Path::new(source_dir);
+
+        // when
+        pack_dir(
+            source_dir_path,
+            target_file,
+            &Input::new(target_ext),
+            &Options::default(),
+        )
+        .unwrap();
+
+        // then
+        let is_packed_file_exists = Path::new(target_file).exists();
+
+        // teardown
+        remove_file(target_file).unwrap();
+
+        // assert
+        assert!(is_packed_file_exists)
+    }
+
+    #[test]
+    fn collect_files_should_sort_files_by_path() {
+        // given
+        let source_dir_path = Path::new("./resources/test/main/can_process_recursively");
+
+        // when
+        let files = collect_files(source_dir_path, "txt").unwrap();
+
+        // then
+        let names: Vec<String> = files.iter().map(|path| source_name(path)).collect();
+
+        // assert
+        assert_eq!(
+            names,
+            vec!["source_1", "source_2", "source_3", "source_4", "source_5", "source_6"]
+        )
+    }
+
+    #[test]
+    fn collect_files_should_sort_numbers_naturally() {
+        // given
+        let source_dir = Path::new("./resources/test/main/temp-natural-order");
+        create_dir_all(source_dir).unwrap();
+        for name in ["10.txt", "2.txt", "1.txt", "episode10.txt", "episode2.txt"].iter() {
+            write(source_dir.join(name), "猫").unwrap();
+        }
+
+        // when
+        let files = collect_files(source_dir, "txt").unwrap();
+
+        // then
+        let names: Vec<String> = files.iter().map(|path| source_name(path)).collect();
+
+        // teardown
+        remove_dir_all(source_dir).unwrap();
+
+        // assert
+        assert_eq!(names, vec!["1", "2", "10", "episode2", "episode10"]);
+    }
+
+    #[test]
+    fn can_import_a_file() {
+        // given
+        let source_file = "./resources/test/main/can_process_a_file/source.txt";
+        let docx_file = "./resources/test/main/can_process_a_file/temp-import.docx";
+        let target_file = "./resources/test/main/can_process_a_file/temp-import.txt";
+        let options = Options::default();
+        process_file(
+            Path::new(source_file),
+            docx_file,
+            &Input::new("txt"),
+            Format::Docx,
+            &options,
+        )
+        .unwrap();
+
+        // when
+        import_file(Path::new(docx_file), Path::new(target_file), "docx").unwrap();
+
+        // then
+        let imported = read_to_string(target_file).unwrap();
+
+        // teardown
+        remove_file(docx_file).unwrap();
+        remove_file(target_file).unwrap();
+
+        // assert
+        assert!(imported.contains("[貴族:わたしたち]"))
+    }
+
+    #[test]
+    fn can_import_dir_relative_to_source() {
+        // given
+        let source_dir = Path::new("./resources/test/main/temp-import-source");
+        let target_dir = "./resources/test/main/temp-import-target";
+        create_dir_all(source_dir.join("sub")).unwrap();
+        write(source_dir.join("1.txt"), "　｜猫《ねこ》").unwrap();
+        write(source_dir.join("sub/2.txt"), "　｜犬《いぬ》").unwrap();
+        write(source_dir.join("sub/2.source.txt"), "[犬:いぬ]").unwrap();
+
+        // when
+        import_dir(source_dir, target_dir, "txt", "narou").unwrap();
+
+        // then
+        let target_files = collect_files(Path::new(target_dir), "txt").unwrap();
+
+        // teardown
+        remove_dir_all(source_dir).unwrap();
+        remove_dir_all(target_dir).unwrap();
+
+        // assert
+        assert_eq!(
+            target_files,
+            vec![
+                Path::new(target_dir).join("1.source.txt"),
+                Path::new(target_dir).join("sub/2.source.txt")
+            ]
+        );
+    }
+
+    #[test]
+    fn can_import_a_narou_file() {
+        // given
+        let source_file = "./resources/test/main/can_process_a_file/source.txt";
+        let narou_file = "./resources/test/main/can_process_a_file/temp-narou.txt";
+        let target_file = "./resources/test/main/can_process_a_file/temp-narou.source.txt";
+        let options = Options::default();
+        process_file(
+            Path::new(source_file),
+            narou_file,
+            &Input::new("txt"),
+            Format::Narou,
+            &options,
+        )
+        .unwrap();
+
+        // when
+        import_file(
+            Path::new(narou_file),
+            &import_path(Path::new(narou_file), "narou"),
+            "narou",
+        )
+        .unwrap();
+
+        // then
+        let imported = read_to_string(target_file).unwrap();
+
+        // teardown
+        remove_file(narou_file).unwrap();
+        remove_file(target_file).unwrap();
+
+        // assert
+        assert!(imported.starts_with("世界神の灯火が"));
+        assert!(imported.contains("[貴族:わたしたち]"))
+    }
+
+    #[test]
+    fn can_process_an_aozora_file() {
+        // given
+        let source_file = "./resources/test/entities/file/aozora.txt";
+        let target_file = "./resources/test/main/can_process_a_file/temp-aozora.txt";
+
+        // when
+        process_file(
+            Path::new(source_file),
+            target_file,
+            &Input {
+                dialect: Dialect::Aozora,
+                ..Input::new("txt")
+            },
+            Format::Narou,
+            &Options::default(),
+        )
+        .unwrap();
+
+        // then
+        let processed = read_to_string(target_file).unwrap();
+
+        // teardown
+        remove_file(target_file).unwrap();
+
+        // assert
+        assert!(processed.contains("｜名前《・・》"));
+        assert!(processed.contains("　　どこで"))
+    }
+
+    #[test]
+    fn should_not_save_a_file_which_violates_ruby_limits_in_strict_mode() {
+        // given
+        let source_file = "./resources/test/main/should_not_save_an_invalid_file/source.txt";
+        let target_file = "./resources/test/main/should_not_save_an_invalid_file/temp.txt";
+
+        // when
+        let result = process_file(
+            Path::new(source_file),
+            target_file,
+            &Input {
+                strict: true,
+                ..Input::new("txt")
+            },
+            Format::Narou,
+            &Options::default(),
+        );
+
+        // then
+        let is_target_file_not_exists = !Path::new(target_file).exists();
+
+        // assert
+        match result {
+            Err(FileProcessError::InvalidMarkup(diagnostics)) => assert_eq!(diagnostics.len(), 1),
+            _ => panic!("markup errors should be returned"),
+        }
+        assert!(is_target_file_not_exists)
+    }
+
+    #[test]
+    fn can_process_a_file_with_glossary() {
+        // given
+        let source_file = "./resources/test/main/can_process_a_file/source.txt";
+        let target_file = "./resources/test/main/can_process_a_file/temp-glossary.txt";
+        let input = Input {
+            glossary: Some(Glossary::load("./resources/test/main/can_process_a_file/glossary.toml").unwrap()),
+            glossary_mode: GlossaryMode::First,
+            ..Input::new("txt")
+        };
+
+        // when
+        process_file(
+            Path::new(source_file),
+            target_file,
+            &input,
+            Format::Narou,
+            &Options::default(),
+        )
+        .unwrap();
+
+        // then
+        let processed = read_to_string(target_file).unwrap();
+
+        // teardown
+        remove_file(target_file).unwrap();
+
+        // assert
+        assert_eq!(processed.matches("｜漁師《りょうし》").count(), 1)
+    }
+
+    #[test]
+    fn should_apply_glossary_before_width_normalization() {
+        // given
+        let source_dir = "./resources/test/main/temp-glossary-width";
+        let source_file = "./resources/test/main/temp-glossary-width/source.txt";
+        let target_file = "./resources/test/main/temp-glossary-width/temp.txt";
+        create_dir_all(source_dir).unwrap();
+        write(source_file, "第3皇子が来た。").unwrap();
+        let input = Input {
+            glossary: Some(Glossary::from_tsv("第3皇子\tだいさんおうじ").unwrap()),
+            width: Some(WidthRules::for_format(Format::Narou)),
+            ..Input::new("txt")
+        };
+
+        // when
+        process_file(
+            Path::new(source_file),
+            target_file,
+            &input,
+            Format::Narou,
+            &Options::default(),
+        )
+        .unwrap();
+
+        // then
+        let processed = read_to_string(target_file).unwrap();
+
+        // teardown
+        remove_dir_all(source_dir).unwrap();
+
+        // assert
+        assert_eq!(processed, "　｜第３皇子《だいさんおうじ》が来た。\n")
+    }
+
+    #[test]
+    fn can_extract_glossary_of_a_dir() {
+        // given
+        let source_dir = "./resources/test/main/can_extract_glossary";
+        let target_file = "./resources/test/main/can_extract_glossary/temp.json";
+
+        // when
+        let index = extract_glossary(
+            Path::new(source_dir),
+            Some(target_file),
+            "txt",
+            "json",
+            Dialect::Naromat,
+            &SourceRules::default(),
+        )
+        .unwrap();
+
+        // then
+        let extracted = read_to_string(target_file).unwrap();
+
+        // teardown
+        remove_file(target_file).unwrap();
+
+        // assert
+        assert_eq!(index.conflicts(), vec!["古"]);
+        assert_eq!(index.occurrence("古", "いにしえ").unwrap().count, 2);
+        assert!(extracted.contains(
+            "{\"parent\": \"古\", \"ruby\": \"ふる\", \"count\": 1, \"files\": [\"./resources/test/main/can_extract_glossary/2.txt\"], \"conflict\": true}"
+        ))
+    }
 }